@@ -0,0 +1,508 @@
+//! Module for a database of named algorithms, for OLL, PLL, COLL, ZBLL and F2L cases. A few
+//! are bundled (see `algs.txt` next to this file, which also describes the format), and more
+//! can be loaded from files in the same format.
+//!
+//! Every algorithm says which case it solves, and that gets checked: undo the algorithm on a
+//! solved cube, and whatever's left is the case. That has to look the way the set says it should
+//! (e.g. first two layers solved, with the right OLL shape), and algorithms filed under the same
+//! case have to actually solve the same case.
+
+use std::fmt::{Display, Formatter};
+
+use crate::cube::{AmbigFacelet, Cube, Facelet};
+use crate::last_layer::{auf_moves, recognize_oll, recognize_pll, AUFS, OLL_ALGS, PLL_ALGS};
+use crate::moves::{invert, Amt, ApplyMove, FullMove};
+use crate::shadow::{to_coll, to_cross_and_pairs, to_oll, Slot, ALL_SLOTS};
+use crate::transform::parse_alg;
+
+const BUILTIN: &str = include_str!("algs.txt");
+
+/// Kinds of algorithms; the last layer ones expect the last layer on top, and F2L expects the
+/// cross on the bottom and solves the FR slot
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum AlgSet {
+    Oll,
+    Pll,
+    /// Solves the last layer corners, when the edges are already oriented
+    Coll,
+    /// Solves the whole last layer, when the edges are already oriented
+    Zbll,
+    F2l,
+}
+
+pub const ALL_SETS: [AlgSet; 5] = [
+    AlgSet::Oll,
+    AlgSet::Pll,
+    AlgSet::Coll,
+    AlgSet::Zbll,
+    AlgSet::F2l,
+];
+
+impl<'a> TryFrom<&'a str> for AlgSet {
+    type Error = &'a str;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        ALL_SETS
+            .into_iter()
+            .find(|set| set.to_string().eq_ignore_ascii_case(value))
+            .ok_or(value)
+    }
+}
+
+impl Display for AlgSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AlgSet::Oll => "OLL",
+            AlgSet::Pll => "PLL",
+            AlgSet::Coll => "COLL",
+            AlgSet::Zbll => "ZBLL",
+            AlgSet::F2l => "F2L",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+impl AlgSet {
+    /// Just the part of the cube this set is meant to solve; an algorithm is done when this is
+    /// solved
+    fn project(self, cube: Cube) -> Cube<AmbigFacelet> {
+        match self {
+            AlgSet::Oll => to_oll(cube),
+            AlgSet::Pll | AlgSet::Zbll => cube.into(),
+            AlgSet::Coll => to_coll(cube),
+            AlgSet::F2l => to_cross_and_pairs(cube, &ALL_SLOTS),
+        }
+    }
+
+    /// The U turns that can come after an algorithm; only the last layer sets care whether
+    /// the U face is turned the right way at the end, and only some of those
+    fn post_aufs(self) -> &'static [Option<Amt>] {
+        match self {
+            AlgSet::Pll | AlgSet::Coll | AlgSet::Zbll => &AUFS,
+            AlgSet::Oll | AlgSet::F2l => &AUFS[..1],
+        }
+    }
+}
+
+/// The OLL number for each shape a COLL or ZBLL case can have; that is, every way the corners
+/// can be twisted when the edges are all oriented. O is the shape with no twisted corners, which
+/// doesn't have an OLL number.
+const SHAPES: [(&str, Option<usize>); 8] = [
+    ("H", Some(21)),
+    ("Pi", Some(22)),
+    ("U", Some(23)),
+    ("T", Some(24)),
+    ("L", Some(25)),
+    ("AS", Some(26)),
+    ("S", Some(27)),
+    ("O", None),
+];
+
+/// A single algorithm, and the case it solves
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct AlgEntry {
+    pub set: AlgSet,
+    /// Name of the case; the number for OLL (e.g. "27"), the name for PLL (e.g. "T"), the
+    /// shape and then anything for COLL and ZBLL (e.g. "S 1"), and anything for F2L
+    pub case: String,
+    pub alg: String,
+}
+
+impl Display for AlgEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{} {}] {}", self.set, self.case, self.alg)
+    }
+}
+
+fn solved() -> Cube {
+    Cube::make_solved(Facelet::Green, Facelet::Yellow)
+}
+
+impl AlgEntry {
+    pub fn moves(&self) -> Vec<FullMove> {
+        parse_alg(&self.alg)
+    }
+
+    /// The case this algorithm solves; a solved cube (yellow on top, green in front) with the
+    /// algorithm undone
+    pub fn case_cube(&self) -> Cube {
+        solved().apply_many(&invert(&self.moves()))
+    }
+
+    /// Every way the case can look, up to turning the U face before and (if the set cares)
+    /// after, projected down to what the set solves
+    fn case_variants(&self) -> Vec<Cube<AmbigFacelet>> {
+        let undo = invert(&self.moves());
+        let mut out = Vec::new();
+
+        for &post in self.set.post_aufs() {
+            for pre in AUFS {
+                let cube = solved()
+                    .apply_many(&auf_moves(post))
+                    .apply_many(&undo)
+                    .apply_many(&auf_moves(pre));
+                out.push(self.set.project(cube));
+            }
+        }
+
+        out
+    }
+
+    /// Checks that the case this algorithm solves is the one it claims to; the error says
+    /// what's wrong, if anything
+    pub fn verify(&self) -> Result<(), String> {
+        let case = self.case_cube();
+        let fail = |why: &str| Err(format!("{self}: {why}"));
+
+        if self.case_variants().iter().any(|c| c.is_solved()) {
+            return fail("algorithm doesn't do anything");
+        }
+
+        if self.set == AlgSet::F2l {
+            let others = [Slot::FL, Slot::BL, Slot::BR];
+            if !to_cross_and_pairs(case, &others).is_solved() {
+                return fail("algorithm should only touch the FR slot and the last layer");
+            }
+            return Ok(());
+        }
+
+        if !to_cross_and_pairs(case.clone(), &ALL_SLOTS).is_solved() {
+            return fail("algorithm should keep the first two layers");
+        }
+
+        let oll_number = recognize_oll(&case).map(|oll| oll.number);
+
+        match self.set {
+            AlgSet::Oll => {
+                if oll_number.map(|n| n.to_string()).as_ref() != Some(&self.case) {
+                    return fail(&format!("actually solves OLL {oll_number:?}"));
+                }
+            }
+            AlgSet::Pll => {
+                if oll_number.is_some() {
+                    return fail("algorithm should keep the last layer oriented");
+                }
+
+                let pll = recognize_pll(&case).unwrap();
+                if pll.name != self.case {
+                    return fail(&format!("actually solves {pll}"));
+                }
+            }
+            AlgSet::Coll | AlgSet::Zbll => {
+                let oriented = [&case.u.bc, &case.u.lc, &case.u.rc, &case.u.fc]
+                    .iter()
+                    .all(|&edge| edge == &case.u.cc);
+                if !oriented {
+                    return fail("algorithm should keep the last layer edges oriented");
+                }
+
+                let shape = self.case.split_whitespace().next().unwrap_or_default();
+                let expected = SHAPES.iter().find(|(name, _)| *name == shape);
+
+                match expected {
+                    None => return fail("case should start with its shape, e.g. S or Pi"),
+                    Some((_, None)) if self.set == AlgSet::Zbll => {
+                        return fail("O cases are just PLLs, not ZBLLs");
+                    }
+                    Some((_, number)) if *number != oll_number => {
+                        return fail(&format!("actually has the shape of OLL {oll_number:?}"));
+                    }
+                    _ => {}
+                }
+            }
+            AlgSet::F2l => unreachable!(),
+        }
+
+        Ok(())
+    }
+}
+
+/// A pile of algorithms, which can be searched by case
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct AlgDatabase {
+    entries: Vec<AlgEntry>,
+}
+
+impl<'a> TryFrom<&'a str> for AlgDatabase {
+    type Error = String;
+
+    /// Reads algorithms in the format of `algs.txt`; this doesn't verify them
+    fn try_from(text: &'a str) -> Result<Self, Self::Error> {
+        let mut entries = Vec::new();
+        let mut current: Option<(AlgSet, String)> = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let header = header
+                    .strip_suffix(']')
+                    .ok_or_else(|| format!("Line {}: header should end with ]", i + 1))?;
+                let (set, case) = header
+                    .trim()
+                    .split_once(' ')
+                    .ok_or_else(|| format!("Line {}: header should be [SET case]", i + 1))?;
+                let set = AlgSet::try_from(set)
+                    .map_err(|set| format!("Line {}: unknown set {set}", i + 1))?;
+
+                current = Some((set, case.trim().to_string()));
+                continue;
+            }
+
+            let Some((set, case)) = &current else {
+                return Err(format!("Line {}: algorithm before any header", i + 1));
+            };
+
+            entries.push(AlgEntry {
+                set: *set,
+                case: case.clone(),
+                alg: line.to_string(),
+            });
+        }
+
+        Ok(AlgDatabase { entries })
+    }
+}
+
+impl AlgDatabase {
+    /// The algorithms that come with the program: the ones [crate::last_layer] uses, plus
+    /// everything in `algs.txt`
+    pub fn builtin() -> Self {
+        let oll = OLL_ALGS.iter().enumerate().map(|(i, (_, alg))| AlgEntry {
+            set: AlgSet::Oll,
+            case: (i + 1).to_string(),
+            alg: alg.to_string(),
+        });
+        let pll = PLL_ALGS.iter().map(|(name, alg)| AlgEntry {
+            set: AlgSet::Pll,
+            case: name.to_string(),
+            alg: alg.to_string(),
+        });
+
+        let mut out = AlgDatabase {
+            entries: oll.chain(pll).collect(),
+        };
+        out.extend(AlgDatabase::try_from(BUILTIN).unwrap());
+        out
+    }
+
+    /// Reads a file of algorithms, and verifies every one of them
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        let out = AlgDatabase::try_from(text.as_str()).map_err(|e| format!("{path}: {e}"))?;
+
+        out.entries.iter().try_for_each(AlgEntry::verify)?;
+
+        Ok(out)
+    }
+
+    pub fn extend(&mut self, other: AlgDatabase) {
+        self.entries.extend(other.entries);
+    }
+
+    pub fn entries(&self) -> &[AlgEntry] {
+        &self.entries
+    }
+
+    /// Every algorithm for the named case
+    pub fn lookup(&self, set: AlgSet, case: &str) -> Vec<&AlgEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.set == set && entry.case == case)
+            .collect()
+    }
+
+    /// Every algorithm in the set which works on this cube, along with the moves to do; that
+    /// is, the algorithm with whatever U turns it needs before and after
+    pub fn solutions(&self, set: AlgSet, cube: &Cube) -> Vec<(&AlgEntry, Vec<FullMove>)> {
+        let mut out = Vec::new();
+
+        for entry in self.entries.iter().filter(|entry| entry.set == set) {
+            let alg = entry.moves();
+
+            let found = AUFS
+                .iter()
+                .flat_map(|&pre| set.post_aufs().iter().map(move |&post| (pre, post)))
+                .map(|(pre, post)| [auf_moves(pre), alg.clone(), auf_moves(post)].concat())
+                .find(|moves| set.project(cube.clone().apply_many(moves)).is_solved());
+
+            if let Some(moves) = found {
+                out.push((entry, moves));
+            }
+        }
+
+        out
+    }
+
+    /// Checks that algorithms filed under the same case solve the same case, and algorithms
+    /// filed under different cases don't; this doesn't verify each algorithm on its own, which
+    /// is what [AlgEntry::verify] is for
+    pub fn check_cases(&self) -> Result<(), String> {
+        let variants: Vec<Vec<Cube<AmbigFacelet>>> =
+            self.entries.iter().map(AlgEntry::case_variants).collect();
+
+        for (i, a) in self.entries.iter().enumerate() {
+            for (j, b) in self.entries.iter().enumerate().take(i) {
+                if a.set != b.set {
+                    continue;
+                }
+
+                let same_case = variants[i].contains(&variants[j][0]);
+
+                if same_case && a.case != b.case {
+                    return Err(format!("{a} and {b} solve the same case"));
+                }
+                if !same_case && a.case == b.case {
+                    return Err(format!("{a} and {b} solve different cases"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::last_layer::PllCase;
+    use crate::moves::parse_many;
+
+    use super::*;
+
+    #[test]
+    fn builtin_algs_are_right() {
+        let db = AlgDatabase::builtin();
+
+        for set in ALL_SETS {
+            assert!(db.entries().iter().any(|entry| entry.set == set));
+        }
+
+        for entry in db.entries() {
+            entry.verify().unwrap();
+            assert!(
+                entry
+                    .set
+                    .project(entry.case_cube().apply_many(&entry.moves()))
+                    .is_solved(),
+                "{entry} should solve its own case"
+            );
+        }
+
+        db.check_cases().unwrap();
+
+        // algs.txt is for alternatives, so nothing in it should already be in the tables
+        let bundled = AlgDatabase::try_from(BUILTIN).unwrap();
+        for entry in bundled.entries() {
+            let table = match entry.set {
+                AlgSet::Oll => &OLL_ALGS[..],
+                AlgSet::Pll => &PLL_ALGS[..],
+                _ => continue,
+            };
+            assert!(
+                table.iter().all(|(_, alg)| *alg != entry.alg),
+                "{entry} is already in the tables"
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_claims_are_caught() {
+        let bad = |text: &str| {
+            let db = AlgDatabase::try_from(text).unwrap();
+            let verified = db.entries().iter().try_for_each(AlgEntry::verify);
+            verified.and_then(|_| db.check_cases())
+        };
+
+        // a sune is OLL 27, not 26
+        assert!(bad("[OLL 26]\nR U R' U R U2 R'").is_err());
+        // a T perm is not a J perm
+        assert!(bad("[PLL Ja]\nR U R' U' R' F R2 U' R' U' R U R' F'").is_err());
+        // sexy move doesn't keep F2L
+        assert!(bad("[COLL S 1]\nR U R' U'").is_err());
+        // the wrong shape
+        assert!(bad("[COLL H 1]\nR U R' U R U2 R'").is_err());
+        // flips edges
+        assert!(bad("[ZBLL T 1]\nF R U R' U' F'").is_err());
+        // one name with two different algorithms for its case is fine, but two names for the
+        // same case aren't, and neither is one name whose algorithms solve different cases
+        assert!(bad("[OLL 27]\nR U R' U R U2 R'\n[OLL 27]\nL' U2 L U L' U L").is_ok());
+        assert!(bad("[PLL Ua]\nM2 U M U2 M' U M2\n[PLL Ua]\nR U' R U R U R U' R' U' R2").is_ok());
+        assert!(bad("[COLL S 1]\nR U R' U R U2 R'\n[COLL S 2]\nL' U2 L U L' U L").is_err());
+        assert!(
+            bad("[COLL H 1]\nR U R' U R U' R' U R U2 R'\nF R U R' U' R U R' U' R U R' U' F'")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(AlgDatabase::try_from("R U R'").is_err());
+        assert!(AlgDatabase::try_from("[OLL 27\nR U R' U R U2 R'").is_err());
+        assert!(AlgDatabase::try_from("[XYZ 1]\nR").is_err());
+        assert!(AlgDatabase::try_from("[OLL]\nR").is_err());
+
+        let db = AlgDatabase::try_from("# comment\n\n[oll 27]\n  R U R' U R U2 R'  \n").unwrap();
+        assert_eq!(
+            db.entries(),
+            &[AlgEntry {
+                set: AlgSet::Oll,
+                case: "27".to_string(),
+                alg: "R U R' U R U2 R'".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn lookup_recognized_cases() {
+        let db = AlgDatabase::builtin();
+        let solved = solved();
+
+        let sune = solved
+            .clone()
+            .apply_many(&invert(&parse_alg("R U R' U R U2 R'")));
+        let oll = recognize_oll(&sune).unwrap();
+        let algs = db.lookup(AlgSet::Oll, &oll.number.to_string());
+        assert_eq!(algs.len(), 2);
+
+        let u_perm = solved
+            .clone()
+            .apply_many(&parse_alg("M2 U M U2 M' U M2"))
+            .apply_many(&parse_many("U"));
+        let pll: PllCase = recognize_pll(&u_perm).unwrap();
+        for entry in db.lookup(AlgSet::Pll, pll.name) {
+            let found = db.solutions(AlgSet::Pll, &u_perm);
+            assert!(found.iter().any(|(e, _)| *e == entry));
+        }
+
+        for (entry, moves) in db.solutions(AlgSet::Coll, &sune) {
+            assert!(entry.case.starts_with("S "));
+            assert!(to_coll(sune.clone().apply_many(&moves)).is_solved());
+        }
+        assert!(!db.solutions(AlgSet::Coll, &sune).is_empty());
+    }
+
+    #[test]
+    fn user_files() {
+        // one file per process, so test runs going at the same time don't trip over each other
+        let path =
+            std::env::temp_dir().join(format!("rubiks_cube_user_algs_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        std::fs::write(path, "[PLL T]\nR U R' U' R' F R2 U' R' U' R U R' F'\n").unwrap();
+        let mut db = AlgDatabase::builtin();
+        let before = db.lookup(AlgSet::Pll, "T").len();
+        db.extend(AlgDatabase::from_file(path).unwrap());
+        assert_eq!(db.lookup(AlgSet::Pll, "T").len(), before + 1);
+
+        std::fs::write(path, "[PLL Y]\nR U R' U' R' F R2 U' R' U' R U R' F'\n").unwrap();
+        assert!(AlgDatabase::from_file(path).is_err());
+
+        std::fs::remove_file(path).unwrap();
+        assert!(AlgDatabase::from_file(path).is_err());
+    }
+}
@@ -0,0 +1,303 @@
+//! Module for answering "what does this algorithm do?" -- given a sequence of moves, we work out
+//! where every cubelet ends up (and how it's twisted or flipped on the way), break that down into
+//! cycles, and from there compute the order of the algorithm; that is, how many times you need
+//! to repeat it to get back to solved.
+//!
+//! The orientation conventions are the Thistlethwaite ones from the orientation state modules,
+//! which look a little odd for individual cubelets, but the total twist (or flip) around a cycle
+//! doesn't depend on the convention, and that's all we report.
+
+use std::fmt::{Display, Formatter};
+
+use crate::corner_orientation_state::{CornerOrientation, CornerOrientationState};
+use crate::corner_position_state::{CornerCubelet, CubeCornerPositions};
+use crate::edge_orientation_state::EdgeOrientationState;
+use crate::edge_position_state::{CubeEdgePositions, SideCubelet};
+use crate::moves::{ApplyMove, FullMove};
+use crate::total_position_state::CubePositions;
+
+/// The effect of an algorithm on a solved cube, as a permutation of the cubelets along with the
+/// orientation each cubelet ends up in
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct AlgEffect {
+    pub positions: CubePositions,
+    pub corner_or: CornerOrientationState,
+    pub edge_or: EdgeOrientationState,
+}
+
+/// A cycle of corner cubelets; the cubelet in the first slot goes to the second slot, and
+/// so on, with the last one going back to the first. The twist is the total twist picked up by
+/// a cubelet going once around the cycle.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct CornerCycle {
+    pub slots: Vec<CornerCubelet>,
+    pub twist: CornerOrientation,
+}
+
+/// A cycle of edge cubelets, read the same way as a [CornerCycle]. Flipped means a cubelet going
+/// once around the cycle comes back flipped.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct EdgeCycle {
+    pub slots: Vec<SideCubelet>,
+    pub flipped: bool,
+}
+
+impl AlgEffect {
+    pub fn from_moves(fms: &[FullMove]) -> Self {
+        Self {
+            positions: CubePositions::make_solved().apply_many(fms),
+            corner_or: CornerOrientationState::solved().apply_many(fms),
+            edge_or: EdgeOrientationState::make_solved().apply_many(fms),
+        }
+    }
+
+    /// All the corner cycles which actually do something; cubelets which are left alone are
+    /// not included, but cubelets which are twisted in place are
+    pub fn corner_cycles(&self) -> Vec<CornerCycle> {
+        let corners = &self.positions.corners;
+
+        // dest[i] is the slot that the cubelet which started in slot i ends up in
+        let mut dest = [0_u8; 8];
+        for slot in 0..8 {
+            dest[corners.ind(slot).to_index() as usize] = slot;
+        }
+
+        let names = CubeCornerPositions::make_solved();
+        let mut seen = [false; 8];
+        let mut out = Vec::new();
+
+        for start in 0..8_u8 {
+            if seen[start as usize] {
+                continue;
+            }
+
+            let mut slots = Vec::new();
+            let mut twist = CornerOrientation::Good;
+            let mut next = start;
+
+            while !seen[next as usize] {
+                seen[next as usize] = true;
+                slots.push(names.ind(next));
+                twist = twist + self.corner_or.ind(next);
+                next = dest[next as usize];
+            }
+
+            if slots.len() > 1 || twist != CornerOrientation::Good {
+                out.push(CornerCycle { slots, twist });
+            }
+        }
+
+        out
+    }
+
+    /// All the edge cycles which actually do something; see [AlgEffect::corner_cycles]
+    pub fn edge_cycles(&self) -> Vec<EdgeCycle> {
+        let edges = &self.positions.edges;
+
+        let mut dest = [0_u8; 12];
+        for slot in 0..12 {
+            dest[edges.ind(slot).to_index() as usize] = slot;
+        }
+
+        let names = CubeEdgePositions::make_solved();
+        let mut seen = [false; 12];
+        let mut out = Vec::new();
+
+        for start in 0..12_u8 {
+            if seen[start as usize] {
+                continue;
+            }
+
+            let mut slots = Vec::new();
+            let mut flipped = false;
+            let mut next = start;
+
+            while !seen[next as usize] {
+                seen[next as usize] = true;
+                slots.push(names.ind(next));
+                // the state stores "is good," so a bad edge is a flip
+                flipped ^= !self.edge_or.ind(next);
+                next = dest[next as usize];
+            }
+
+            if slots.len() > 1 || flipped {
+                out.push(EdgeCycle { slots, flipped });
+            }
+        }
+
+        out
+    }
+
+    /// Number of times the algorithm needs to be repeated to get back to solved. Each cycle
+    /// comes back after its length, unless it picks up a twist or flip, in which case it needs
+    /// to go around three (or two) times; then the whole thing comes back at the LCM of those.
+    pub fn order(&self) -> usize {
+        let corner_periods = self.corner_cycles().into_iter().map(|c| {
+            if c.twist == CornerOrientation::Good {
+                c.slots.len()
+            } else {
+                c.slots.len() * 3
+            }
+        });
+
+        let edge_periods = self.edge_cycles().into_iter().map(|c| {
+            if c.flipped {
+                c.slots.len() * 2
+            } else {
+                c.slots.len()
+            }
+        });
+
+        corner_periods.chain(edge_periods).fold(1, lcm)
+    }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    fn gcd(a: usize, b: usize) -> usize {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    a / gcd(a, b) * b
+}
+
+impl Display for CornerCycle {
+    /// Formats like `(FUL BUL BUR)+`, where a trailing + is a net CW twist and - is a net CCW one
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<String> = self.slots.iter().map(|c| format!("{c:?}")).collect();
+        write!(f, "({})", names.join(" "))?;
+
+        match self.twist {
+            CornerOrientation::Good => {}
+            CornerOrientation::CW => write!(f, "+")?,
+            CornerOrientation::CCW => write!(f, "-")?,
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for EdgeCycle {
+    /// Formats like `(UF UL UB)`, with a trailing + if the cycle flips its edges
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<String> = self.slots.iter().map(|c| format!("{c:?}")).collect();
+        write!(f, "({})", names.join(" "))?;
+
+        if self.flipped {
+            write!(f, "+")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for AlgEffect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let corners: Vec<String> = self.corner_cycles().iter().map(|c| c.to_string()).collect();
+        let edges: Vec<String> = self.edge_cycles().iter().map(|c| c.to_string()).collect();
+
+        writeln!(f, "Corners: {}", corners.join(" "))?;
+        writeln!(f, "Edges: {}", edges.join(" "))?;
+        write!(f, "Order: {}", self.order())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cube::{Cube, Facelet};
+    use crate::moves::parse_many;
+
+    use super::*;
+
+    fn effect(alg: &str) -> AlgEffect {
+        AlgEffect::from_moves(&parse_many(alg))
+    }
+
+    /// The slow and obviously correct way; keep doing it until the cube is solved again
+    fn brute_force_order(alg: &str) -> usize {
+        let moves = parse_many(alg);
+        let mut cube = Cube::make_solved(Facelet::Green, Facelet::Yellow).apply_many(&moves);
+        let mut order = 1;
+
+        while !cube.is_solved() {
+            cube = cube.apply_many(&moves);
+            order += 1;
+        }
+
+        order
+    }
+
+    #[test]
+    fn known_orders() {
+        assert_eq!(effect("").order(), 1);
+        assert_eq!(effect("R").order(), 4);
+        assert_eq!(effect("R2").order(), 2);
+        assert_eq!(effect("R U R' U'").order(), 6);
+        assert_eq!(effect("R U").order(), 105);
+    }
+
+    #[test]
+    fn orders_match_cube_model() {
+        for alg in [
+            "R U R' U'",
+            "R U",
+            "R U2 R' U' R U' R'",
+            "F R U R' U' F'",
+            "R U R' U' R' F R2 U' R' U' R U R' F'",
+            "U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2",
+            "B U F' L U R' L' F2 D' F2 L F' R' D L' D U2 R' U2 F' D' R2 F2 B' U2",
+        ] {
+            assert_eq!(
+                effect(alg).order(),
+                brute_force_order(alg),
+                "Order of {alg}"
+            );
+        }
+    }
+
+    #[test]
+    fn quarter_turn_cycles() {
+        let e = effect("U");
+
+        let corners = e.corner_cycles();
+        assert_eq!(corners.len(), 1);
+        assert_eq!(corners[0].to_string(), "(FUL BUL BUR FUR)");
+
+        let edges = e.edge_cycles();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].to_string(), "(UL UB UR UF)");
+    }
+
+    #[test]
+    fn superflip_flips_everything() {
+        let e = effect("U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2");
+
+        assert!(e.corner_cycles().is_empty());
+
+        let edges = e.edge_cycles();
+        assert_eq!(edges.len(), 12);
+        assert!(edges.iter().all(|c| c.slots.len() == 1 && c.flipped));
+
+        assert_eq!(e.order(), 2);
+    }
+
+    #[test]
+    fn t_perm_swaps() {
+        let e = effect("R U R' U' R' F R2 U' R' U' R U R' F'");
+
+        let corners = e.corner_cycles();
+        assert_eq!(corners.len(), 1);
+        assert_eq!(corners[0].slots.len(), 2);
+        assert_eq!(corners[0].twist, CornerOrientation::Good);
+
+        let edges = e.edge_cycles();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].slots.len(), 2);
+        assert!(!edges[0].flipped);
+
+        assert_eq!(e.order(), 2);
+    }
+}
@@ -0,0 +1,473 @@
+//! Module for solving a cube the way it's usually taught to beginners: layer by layer, one piece
+//! at a time, with a handful of algorithms. The cross edges go in one at a time (each as quickly
+//! as possible without breaking the ones already there), and everything after that is done only
+//! with the algorithms below. It's slow, but it's deterministic, and every step can be explained.
+
+use std::fmt::{Display, Formatter};
+
+use crate::cube::{Cube, Facelet};
+use crate::last_layer::{auf_moves, AUFS};
+use crate::moves::{parse_many, to_nice_str, Amt, ApplyMove, FullMove};
+use crate::shadow::{Keep, MaskSpec, Piece};
+use crate::solve::{rotation_to_bottom, solve_mask, MaskCache};
+use crate::transform::{rotate, Axis, Rotation};
+
+/// One of the algorithms a beginner is expected to know. Each is written for the front right
+/// slot, or for the last layer held in front.
+struct Alg {
+    name: &'static str,
+    moves: &'static str,
+}
+
+/// Takes the front right corner out of its slot and back again; done enough times, it puts the
+/// corner above the slot into it, the right way around
+const SEXY: Alg = Alg {
+    name: "R U R' U'",
+    moves: "R U R' U'",
+};
+
+/// Puts the edge at UF into the front right slot
+const RIGHT_EDGE: Alg = Alg {
+    name: "right edge insert",
+    moves: "U R U' R' U' F' U F",
+};
+
+/// Puts the edge at UR into the front right slot
+const LEFT_EDGE: Alg = Alg {
+    name: "left edge insert",
+    moves: "U' F' U F U R U' R'",
+};
+
+/// Orients two or four last layer edges at a time; a dot becomes an L, an L (at the back left)
+/// becomes a line, and a (horizontal) line becomes the cross
+const YELLOW_CROSS: Alg = Alg {
+    name: "F R U R' U' F'",
+    moves: "F R U R' U' F'",
+};
+
+/// Twists three last layer corners
+const SUNE: Alg = Alg {
+    name: "Sune",
+    moves: "R U R' U R U2 R'",
+};
+
+/// Cycles three last layer corners, keeping them oriented
+const A_PERM: Alg = Alg {
+    name: "A-perm",
+    moves: "R' F R' B2 R F' R' B2 R2",
+};
+
+/// Cycles three last layer edges, keeping them oriented
+const U_PERM: Alg = Alg {
+    name: "U-perm",
+    moves: "R U' R U R U R U' R' U' R2",
+};
+
+/// The four slots, along with the rotation which takes the front right slot to each of them
+const SLOTS: [(&str, Option<Amt>); 4] = [
+    ("FR", None),
+    ("BR", Some(Amt::One)),
+    ("BL", Some(Amt::Two)),
+    ("FL", Some(Amt::Rev)),
+];
+
+const CROSS_EDGES: [&str; 4] = ["DF", "DR", "DB", "DL"];
+
+fn slot_rotation(amt: Option<Amt>) -> Option<Rotation> {
+    amt.map(|amt| Rotation { axis: Axis::Y, amt })
+}
+
+/// An algorithm, done from some angle some number of times, after an AUF
+struct Step {
+    auf: Option<Amt>,
+    alg: &'static Alg,
+    slot: usize,
+    times: usize,
+}
+
+impl Step {
+    fn moves(&self) -> Vec<FullMove> {
+        let mut alg = parse_many(self.alg.moves);
+        if let Some(rotation) = slot_rotation(SLOTS[self.slot].1) {
+            alg = rotate(&alg, rotation);
+        }
+
+        let mut out = auf_moves(self.auf);
+        for _ in 0..self.times {
+            out.extend(alg.iter().copied());
+        }
+        out
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.auf.is_some() {
+            write!(f, "{}, then ", to_nice_str(&auf_moves(self.auf)))?;
+        }
+
+        write!(f, "{}", self.alg.name)?;
+
+        if self.slot != 0 {
+            write!(f, " at {}", SLOTS[self.slot].0)?;
+        }
+
+        if self.times > 1 {
+            write!(f, " ({} times)", self.times)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the fewest steps, each made of one of the options (an algorithm, which slot to do it
+/// at, and how many times), which make `done` true. Everything is tried in a fixed order, so the
+/// same cube always gets the same steps.
+fn find_steps(
+    cube: &Cube,
+    options: &[(&'static Alg, usize, usize)],
+    max_steps: usize,
+    done: impl Fn(&Cube) -> bool,
+) -> Option<Vec<Step>> {
+    fn search(
+        cube: &Cube,
+        options: &[(&'static Alg, usize, usize)],
+        running: &mut Vec<Step>,
+        depth: usize,
+        done: &impl Fn(&Cube) -> bool,
+    ) -> bool {
+        if running.len() == depth {
+            return done(cube);
+        }
+
+        for auf in AUFS {
+            for &(alg, slot, times) in options {
+                let step = Step {
+                    auf,
+                    alg,
+                    slot,
+                    times,
+                };
+                let next = cube.clone().apply_many(&step.moves());
+
+                running.push(step);
+                if search(&next, options, running, depth, done) {
+                    return true;
+                }
+                running.pop();
+            }
+        }
+
+        false
+    }
+
+    (0..=max_steps).find_map(|depth| {
+        let mut running = Vec::new();
+        search(cube, options, &mut running, depth, &done).then_some(running)
+    })
+}
+
+/// One stage of a beginner solve: what it's for, how it was done, and the moves
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct BeginnerStage {
+    pub name: String,
+    pub note: String,
+    pub moves: Vec<FullMove>,
+}
+
+/// A whole beginner solve; do the rotation (if any) first, to put the cross on the bottom, then
+/// the moves of each stage in order
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct BeginnerSolution {
+    pub rotation: Option<Rotation>,
+    pub stages: Vec<BeginnerStage>,
+}
+
+impl BeginnerSolution {
+    /// Every move of the solve, stage after stage
+    pub fn moves(&self) -> Vec<FullMove> {
+        self.stages
+            .iter()
+            .flat_map(|stage| stage.moves.iter().copied())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.iter().map(|stage| stage.moves.len()).sum()
+    }
+}
+
+impl Display for BeginnerSolution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(rotation) = self.rotation {
+            writeln!(f, "Rotation: {rotation}")?;
+        }
+
+        for (i, stage) in self.stages.iter().enumerate() {
+            writeln!(
+                f,
+                "{}. {} ({} moves): {}",
+                i + 1,
+                stage.name,
+                stage.moves.len(),
+                to_nice_str(&stage.moves)
+            )?;
+            writeln!(f, "   {}", stage.note)?;
+        }
+
+        write!(f, "Total: {} moves", self.len())
+    }
+}
+
+/// Heuristics for putting in the cross edges, one more each time
+pub struct BeginnerCache {
+    cross: Vec<MaskCache>,
+}
+
+impl BeginnerCache {
+    pub fn initialize() -> Self {
+        let mut spec = MaskSpec::new();
+
+        let cross = CROSS_EDGES
+            .iter()
+            .map(|&edge| {
+                spec.keep(Piece::try_from(edge).unwrap(), Keep::Solved);
+                MaskCache::initialize(&spec)
+            })
+            .collect();
+
+        Self { cross }
+    }
+}
+
+/// Works through one stage with the given algorithm options, adding it to the solve
+fn alg_stage(
+    held: &mut Cube,
+    stages: &mut Vec<BeginnerStage>,
+    name: String,
+    about: &str,
+    options: &[(&'static Alg, usize, usize)],
+    max_steps: usize,
+    done: impl Fn(&Cube) -> bool,
+) {
+    let steps = find_steps(held, options, max_steps, done)
+        .unwrap_or_else(|| panic!("Couldn't do '{name}' with the beginner algorithms, weird"));
+
+    let moves: Vec<FullMove> = steps.iter().flat_map(Step::moves).collect();
+    *held = held.clone().apply_many(&moves);
+
+    let note = if steps.is_empty() {
+        format!("{about}; already done")
+    } else {
+        let how: Vec<String> = steps.iter().map(|step| step.to_string()).collect();
+        format!("{about}: {}", how.join("; "))
+    };
+
+    stages.push(BeginnerStage { name, note, moves });
+}
+
+/// Does some AUF make `done` true?
+fn after_some_auf(cube: &Cube, done: impl Fn(&Cube) -> bool) -> bool {
+    AUFS.iter()
+        .any(|&auf| done(&cube.clone().apply_many(&auf_moves(auf))))
+}
+
+/// Solves the cube layer by layer, with the cross on the given color
+pub fn solve_beginner(cube: &Cube, color: &Facelet, cache: &BeginnerCache) -> BeginnerSolution {
+    let rotation = rotation_to_bottom(cube, color);
+
+    let mut held = match rotation {
+        Some(rotation) => cube.rotated(rotation),
+        None => cube.clone(),
+    };
+
+    let mut stages = Vec::new();
+
+    // everything solved so far
+    let mut spec = MaskSpec::new();
+
+    for (&edge, cross_cache) in CROSS_EDGES.iter().zip(&cache.cross) {
+        spec.keep(Piece::try_from(edge).unwrap(), Keep::Solved);
+
+        let moves = solve_mask(&held, cross_cache, 8);
+        held = held.apply_many(&moves);
+
+        stages.push(BeginnerStage {
+            name: format!("Cross edge {edge}"),
+            note: "Bring the edge down next to its center, without breaking the cross edges \
+                   already there"
+                .to_string(),
+            moves,
+        });
+    }
+
+    for (slot, &(name, _)) in SLOTS.iter().enumerate() {
+        let corner = Piece::try_from(format!("D{name}").as_str()).unwrap();
+        spec.keep(corner, Keep::Solved);
+
+        // take the corner out of whichever slot it's stuck in, if any, then put it above this
+        // slot and repeat R U R' U' until it's in
+        let mut options: Vec<_> = (1..=5).map(|times| (&SEXY, slot, times)).collect();
+        options.extend(
+            (0..SLOTS.len())
+                .filter(|&s| s != slot)
+                .map(|s| (&SEXY, s, 1)),
+        );
+
+        alg_stage(
+            &mut held,
+            &mut stages,
+            format!("First layer corner {corner}"),
+            "Put the corner above its slot, then R U R' U' until it drops in",
+            &options,
+            2,
+            |c| spec.apply(c.clone()).is_solved(),
+        );
+    }
+
+    for (slot, &(name, _)) in SLOTS.iter().enumerate() {
+        let edge = Piece::try_from(name).unwrap();
+        spec.keep(edge, Keep::Solved);
+
+        // if it's stuck in the wrong slot (or the right slot, but flipped) an insert pops it
+        // out again
+        let mut options = vec![(&RIGHT_EDGE, slot, 1), (&LEFT_EDGE, slot, 1)];
+        options.extend(
+            (0..SLOTS.len())
+                .filter(|&s| s != slot)
+                .map(|s| (&RIGHT_EDGE, s, 1)),
+        );
+
+        alg_stage(
+            &mut held,
+            &mut stages,
+            format!("Second layer edge {edge}"),
+            "Line the edge up with its center on top, then insert it to the left or right",
+            &options,
+            2,
+            |c| spec.apply(c.clone()).is_solved(),
+        );
+    }
+
+    for edge in ["UF", "UR", "UB", "UL"] {
+        spec.keep(Piece::try_from(edge).unwrap(), Keep::Oriented);
+    }
+
+    alg_stage(
+        &mut held,
+        &mut stages,
+        "Yellow cross".to_string(),
+        "Orient the last layer edges, from a dot to an L to a line to the cross",
+        &[(&YELLOW_CROSS, 0, 1)],
+        3,
+        |c| spec.apply(c.clone()).is_solved(),
+    );
+
+    for corner in ["UFL", "UFR", "UBL", "UBR"] {
+        spec.keep(Piece::try_from(corner).unwrap(), Keep::Oriented);
+    }
+
+    alg_stage(
+        &mut held,
+        &mut stages,
+        "Orient corners".to_string(),
+        "Twist the last layer corners with Sune until the whole top is one color",
+        &[(&SUNE, 0, 1)],
+        4,
+        |c| spec.apply(c.clone()).is_solved(),
+    );
+
+    for corner in ["UFL", "UFR", "UBL", "UBR"] {
+        spec.keep(Piece::try_from(corner).unwrap(), Keep::Solved);
+    }
+
+    alg_stage(
+        &mut held,
+        &mut stages,
+        "Permute corners".to_string(),
+        "Cycle the last layer corners with A-perms until they're all in the right places",
+        &[(&A_PERM, 0, 1)],
+        2,
+        |c| after_some_auf(c, |c| spec.apply(c.clone()).is_solved()),
+    );
+
+    alg_stage(
+        &mut held,
+        &mut stages,
+        "Permute edges".to_string(),
+        "Cycle the last layer edges with U-perms until they're all in the right places",
+        &[(&U_PERM, 0, 1)],
+        2,
+        |c| after_some_auf(c, Cube::is_solved),
+    );
+
+    let auf = AUFS
+        .into_iter()
+        .find(|&auf| held.clone().apply_many(&auf_moves(auf)).is_solved())
+        .expect("Should be solved after an AUF");
+    let moves = auf_moves(auf);
+    held = held.apply_many(&moves);
+
+    stages.push(BeginnerStage {
+        name: "AUF".to_string(),
+        note: "Turn the top to finish".to_string(),
+        moves,
+    });
+
+    assert!(held.is_solved(), "Cube should be solved, that's the point");
+
+    BeginnerSolution { rotation, stages }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_and_explains() {
+        let cache = BeginnerCache::initialize();
+
+        let scrambles = [
+            "R U2 F' L D2 B R' U F2 L' D B2 U'",
+            "D2 F' R2 U B' L D' F2 R U2 L' B D R'",
+            "L2 B R' D F2 U' R B2 L' U F D2 R2 B'",
+        ];
+
+        for scramble in scrambles {
+            let cube =
+                Cube::make_solved(Facelet::Green, Facelet::White).apply_many(&parse_many(scramble));
+
+            let sol = solve_beginner(&cube, &Facelet::White, &cache);
+
+            let held = cube.clone().rotated(sol.rotation.unwrap());
+            assert!(held.apply_many(&sol.moves()).is_solved(), "{scramble}");
+
+            // 4 cross edges, 4 corners, 4 edges, then the last layer
+            let names: Vec<&str> = sol.stages.iter().map(|s| s.name.as_str()).collect();
+            assert_eq!(names.len(), 17);
+            assert_eq!(names[0], "Cross edge DF");
+            assert_eq!(names[4], "First layer corner DFR");
+            assert_eq!(names[8], "Second layer edge FR");
+            assert_eq!(names[16], "AUF");
+
+            // and the same cube always gets the same solution
+            assert_eq!(sol, solve_beginner(&cube, &Facelet::White, &cache));
+        }
+    }
+
+    #[test]
+    fn skips_what_is_solved() {
+        let cache = BeginnerCache::initialize();
+
+        // only needs the edges permuted
+        let cube = Cube::make_solved(Facelet::Green, Facelet::Yellow)
+            .apply_many(&parse_many(U_PERM.moves));
+
+        let sol = solve_beginner(&cube, &Facelet::White, &cache);
+
+        assert_eq!(sol.rotation, None);
+        assert!(sol.stages[..15].iter().all(|s| s.moves.is_empty()));
+        assert!(sol.stages[15].note.contains("U-perm"));
+        assert!(cube.apply_many(&sol.moves()).is_solved());
+    }
+}
@@ -0,0 +1,192 @@
+//! Module for solving a cube the way a person would with CFOP: first the cross, then the four
+//! F2L pairs one at a time, then OLL and PLL. The cross and each pair are solved optimally (given
+//! what's already solved), and the last layer is solved with the usual algorithms, so this is
+//! something like the best a very good human could do without planning ahead.
+
+use std::fmt::{Display, Formatter};
+
+use crate::cube::{Cube, Facelet};
+use crate::last_layer::{auf_moves, recognize_auf, recognize_oll, recognize_pll};
+use crate::moves::{to_nice_str, ApplyMove, FullMove};
+use crate::shadow::{to_cross, to_cross_and_pairs, Slot, ALL_SLOTS};
+use crate::solve::{solve_cross, solve_xcross, XCrossCache};
+use crate::transform::Rotation;
+
+/// One stage of a CFOP solve, along with the moves for it
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Stage {
+    pub name: String,
+    pub moves: Vec<FullMove>,
+}
+
+/// A whole CFOP solve; do the rotation (if any) first, to put the cross on the bottom, then the
+/// moves of each stage in order
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CfopSolution {
+    pub rotation: Option<Rotation>,
+    pub stages: Vec<Stage>,
+}
+
+impl CfopSolution {
+    /// Every move of the solve, stage after stage
+    pub fn moves(&self) -> Vec<FullMove> {
+        self.stages
+            .iter()
+            .flat_map(|stage| stage.moves.iter().copied())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.iter().map(|stage| stage.moves.len()).sum()
+    }
+}
+
+impl Display for CfopSolution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(rotation) = self.rotation {
+            writeln!(f, "Rotation: {rotation}")?;
+        }
+
+        for stage in &self.stages {
+            writeln!(
+                f,
+                "{} ({} moves): {}",
+                stage.name,
+                stage.moves.len(),
+                to_nice_str(&stage.moves)
+            )?;
+        }
+
+        write!(f, "Total: {} moves", self.len())
+    }
+}
+
+/// Finds the pre-AUF and OLL algorithm which orient the last layer; nothing, if it's already
+/// oriented
+fn solve_oll(cube: &Cube) -> (String, Vec<FullMove>) {
+    match recognize_oll(cube) {
+        Some(case) => (case.to_string(), case.moves()),
+        None => ("OLL skip".to_string(), Vec::new()),
+    }
+}
+
+/// Finds the pre-AUF, PLL algorithm and post-AUF which solve the cube; just the AUF, if the last
+/// layer is already permuted
+fn solve_pll(cube: &Cube) -> (String, Vec<FullMove>) {
+    match recognize_pll(cube) {
+        Some(case) => (format!("PLL {case}"), case.moves()),
+        None => {
+            let auf = recognize_auf(cube).expect("Permuted last layer should just need an AUF");
+            ("PLL skip".to_string(), auf_moves(auf))
+        }
+    }
+}
+
+/// Solves the cube with CFOP, with the cross on the given color
+pub fn solve_cfop(cube: &Cube, color: &Facelet, cache: &XCrossCache) -> CfopSolution {
+    let mut stages = Vec::new();
+
+    let cross = solve_cross(cube, color, cache.cross());
+    let rotation = cross.rotation;
+
+    let mut held = match rotation {
+        Some(rotation) => cube.rotated(rotation),
+        None => cube.clone(),
+    };
+
+    held = held.apply_many(&cross.moves);
+    assert!(to_cross(held.clone(), color).is_solved());
+    stages.push(Stage {
+        name: "Cross".to_string(),
+        moves: cross.moves,
+    });
+
+    // each pair goes in optimally, keeping everything already solved; whichever pair is
+    // quickest goes in next
+    let mut done: Vec<Slot> = Vec::new();
+
+    while done.len() < ALL_SLOTS.len() {
+        let (slot, moves) = ALL_SLOTS
+            .iter()
+            .filter(|slot| !done.contains(slot))
+            .map(|&slot| {
+                let slots = [done.clone(), vec![slot]].concat();
+                let sol = solve_xcross(&held, color, &slots, cache);
+                assert_eq!(sol.rotation, None, "Cross should already be on the bottom");
+                (slot, sol.moves)
+            })
+            .min_by_key(|(_, moves)| moves.len())
+            .unwrap();
+
+        done.push(slot);
+        held = held.apply_many(&moves);
+        assert!(to_cross_and_pairs(held.clone(), &done).is_solved());
+
+        stages.push(Stage {
+            name: format!("F2L {} ({slot:?})", done.len()),
+            moves,
+        });
+    }
+
+    let (name, moves) = solve_oll(&held);
+    held = held.apply_many(&moves);
+    stages.push(Stage { name, moves });
+
+    let (name, moves) = solve_pll(&held);
+    held = held.apply_many(&moves);
+    stages.push(Stage { name, moves });
+
+    assert!(held.is_solved(), "Cube should be solved, that's the point");
+
+    CfopSolution { rotation, stages }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::moves::parse_many;
+    use crate::transform::parse_alg;
+
+    use super::*;
+
+    #[test]
+    fn solves_and_splits_stages() {
+        let cache = XCrossCache::initialize();
+
+        let cube = Cube::make_solved(Facelet::Green, Facelet::White)
+            .apply_many(&parse_many("R U2 F' L D2 B R' U F2 L' D B2 U'"));
+
+        let sol = solve_cfop(&cube, &Facelet::White, &cache);
+
+        let names: Vec<&str> = sol.stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names.len(), 7);
+        assert_eq!(names[0], "Cross");
+        assert!(names[1..5].iter().all(|name| name.starts_with("F2L")));
+        assert!(names[5].starts_with("OLL"));
+        assert!(names[6].starts_with("PLL"));
+
+        // white starts on top, so it has to be flipped down
+        assert_eq!(sol.rotation, Rotation::try_from("x2").ok());
+        let held = cube.rotated(sol.rotation.unwrap());
+        assert!(held.apply_many(&sol.moves()).is_solved());
+
+        assert_eq!(sol.moves().len(), sol.len());
+    }
+
+    #[test]
+    fn last_layer_only() {
+        let cache = XCrossCache::initialize();
+
+        // a sune and then a T perm; everything but the last layer is already done
+        let cube = Cube::make_solved(Facelet::Green, Facelet::Yellow).apply_many(&parse_alg(
+            "R U R' U R U2 R' R U R' U' R' F R2 U' R' U' R U R' F'",
+        ));
+
+        let sol = solve_cfop(&cube, &Facelet::White, &cache);
+
+        assert_eq!(sol.rotation, None);
+        assert!(sol.stages[..5].iter().all(|s| s.moves.is_empty()));
+        assert!(sol.stages[5].name.starts_with("OLL"));
+        assert!(sol.stages[6].name.starts_with("PLL"));
+        assert!(cube.apply_many(&sol.moves()).is_solved());
+    }
+}
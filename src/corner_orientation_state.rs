@@ -6,6 +6,7 @@ use crate::moves::CanMove;
 use std::iter::Sum;
 use std::ops::Add;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub enum CornerOrientation {
     Good,
@@ -89,6 +90,22 @@ impl CornerOrientationState {
         }
     }
 
+    /// Gets the orientation of the cubelet in the given slot, where slots are numbered as in
+    /// [crate::corner_position_state::CornerCubelet::to_index]
+    pub fn ind(&self, index: u8) -> CornerOrientation {
+        match index {
+            0 => self.ful,
+            1 => self.fur,
+            2 => self.bur,
+            3 => self.bul,
+            4 => self.fdl,
+            5 => self.fdr,
+            6 => self.bdr,
+            7 => self.bdl,
+            _ => panic!("Out of range index {index}"),
+        }
+    }
+
     fn total_orientation(&self) -> CornerOrientation {
         [
             self.ful, self.fur, self.fdl, self.fdr, self.bul, self.bur, self.bdl, self.bdr,
@@ -6,6 +6,7 @@ use crate::moves::CanMove;
 
 /// This indicates what the cubelet "is;" or equivalently, where it belongs.
 /// 8 possible values, fits in a byte.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 pub enum CornerCubelet {
     FUL,
@@ -60,7 +61,8 @@ impl CubeCornerPositions {
         }
     }
 
-    fn ind(&self, index: u8) -> CornerCubelet {
+    /// Gets the cubelet in the given slot, where slots are numbered as in [CornerCubelet::to_index]
+    pub fn ind(&self, index: u8) -> CornerCubelet {
         match index {
             0 => self.ful.clone(),
             1 => self.fur.clone(),
@@ -233,6 +235,7 @@ impl CanMove for CubeCornerPositions {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::moves::{parse_many, ApplyMove};
 
     #[test]
     fn solved_test() {
@@ -283,4 +286,16 @@ mod tests {
 
         assert!(!input.directly_solvable());
     }
+
+    #[test]
+    fn moves_match_cube() {
+        let moves = parse_many("B U F' L U R' L' F2 D' F2 L F' R' D L' D U2 R' U2 F' D' R2 F2 B'");
+
+        for i in 0..=moves.len() {
+            let cube = Cube::make_solved(Facelet::Green, Facelet::Yellow).apply_many(&moves[..i]);
+            let state = CubeCornerPositions::make_solved().apply_many(&moves[..i]);
+
+            assert_eq!(CubeCornerPositions::from_cube(&cube), state);
+        }
+    }
 }
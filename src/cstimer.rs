@@ -0,0 +1,279 @@
+//! Module for reading session exports from csTimer. An export is one JSON object, with a key for
+//! each session ("session1", "session2", ...) holding a list of solves, and a "properties" key
+//! which (among other things) has the session names, as a JSON string inside the JSON.
+//!
+//! Each solve looks like `[[penalty, time], scramble, comment, date]`: the time is in
+//! milliseconds and the date in seconds since the Unix epoch. The penalty is 0 for none, 2000 for
+//! +2, and -1 for DNF. Anything after those four is ignored, as is anything extra in the first
+//! list (which is where split times go).
+//!
+//! Each session's options (also in "sessionData") say what kind of scramble it uses, as
+//! "scrType": "333" and its variants are 3x3, and everything else is some other puzzle.
+
+use serde_json::Value;
+
+use crate::cube::{Cube, Facelet};
+use crate::moves::{canonicalize, ApplyMove, FullMove};
+use crate::session::{Penalty, Session, Solve};
+use crate::solve::{best_cross, solve_all_crosses, CrossCache};
+
+/// One session from the export, in the order csTimer numbers them
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CsTimerSession {
+    /// Whatever it was called in csTimer; just the number if it never got a name
+    pub name: String,
+    /// csTimer's name for the kind of scramble, like "333" for 3x3 or "222so" for 2x2; csTimer
+    /// leaves it out for 3x3, so that's what it is if the export doesn't say
+    pub scramble_type: String,
+    pub session: Session,
+}
+
+impl CsTimerSession {
+    /// Whether the scrambles are for a 3x3; the 3x3 types are "333", "333oh", "333fm" and so on
+    pub fn is_3x3(&self) -> bool {
+        self.scramble_type.starts_with("333")
+    }
+}
+
+/// Reads a whole csTimer export. Sessions with no solves are kept, since the numbering might
+/// matter to somebody.
+pub fn parse_export(text: &str) -> Result<Vec<CsTimerSession>, String> {
+    let json: Value = serde_json::from_str(text).map_err(|e| format!("Not valid JSON: {e}"))?;
+    let top = json
+        .as_object()
+        .ok_or("Should be a JSON object, with a key for each session")?;
+
+    // the names are in a string of JSON, keyed by session number
+    let names: Option<Value> = top
+        .get("properties")
+        .and_then(|p| p.get("sessionData"))
+        .and_then(Value::as_str)
+        .and_then(|data| serde_json::from_str(data).ok());
+
+    let mut numbered = Vec::new();
+
+    for (key, solves) in top {
+        let Some(number) = key.strip_prefix("session") else {
+            continue;
+        };
+        let Ok(number) = number.parse::<u32>() else {
+            continue;
+        };
+
+        let solves = solves
+            .as_array()
+            .ok_or_else(|| format!("{key}: should be a list of solves"))?;
+
+        let mut session = Session::default();
+        for (i, solve) in solves.iter().enumerate() {
+            let solve = parse_solve(solve).map_err(|e| format!("{key}, solve {}: {e}", i + 1))?;
+            session.push(solve);
+        }
+
+        let data = names
+            .as_ref()
+            .and_then(|names| names.get(number.to_string()));
+
+        let name = data
+            .and_then(|data| data.get("name"))
+            .map(|name| match name {
+                Value::String(name) => name.clone(),
+                other => other.to_string(),
+            })
+            .unwrap_or_else(|| number.to_string());
+
+        let scramble_type = data
+            .and_then(|data| data.get("opt"))
+            .and_then(|opt| opt.get("scrType"))
+            .and_then(Value::as_str)
+            .unwrap_or("333")
+            .to_string();
+
+        numbered.push((
+            number,
+            CsTimerSession {
+                name,
+                scramble_type,
+                session,
+            },
+        ));
+    }
+
+    numbered.sort_by_key(|(number, _)| *number);
+    Ok(numbered.into_iter().map(|(_, session)| session).collect())
+}
+
+fn parse_solve(solve: &Value) -> Result<Solve, String> {
+    let fields = solve.as_array().ok_or("should be a list")?;
+
+    let result = fields
+        .first()
+        .and_then(Value::as_array)
+        .ok_or("should start with [penalty, time]")?;
+    let penalty = result
+        .first()
+        .and_then(Value::as_i64)
+        .ok_or("penalty should be a number")?;
+    let time = result
+        .get(1)
+        .and_then(Value::as_u64)
+        .ok_or("time should be a number of milliseconds")?;
+
+    let penalty = match penalty {
+        0 => Penalty::Clean,
+        2000 => Penalty::PlusTwo,
+        -1 => Penalty::Dnf,
+        other => return Err(format!("unknown penalty {other}")),
+    };
+
+    let scramble = fields
+        .get(1)
+        .and_then(Value::as_str)
+        .ok_or("scramble should be a string")?;
+    // older exports don't always have a date
+    let date = fields.get(3).and_then(Value::as_u64).unwrap_or(0);
+
+    Ok(Solve {
+        time: std::time::Duration::from_millis(time),
+        penalty,
+        scramble: scramble.to_string(),
+        date,
+    })
+}
+
+/// Reads a scramble the strict way: nothing but face turns, since that's all a 3x3 scramble
+/// should have. Then it's done to a solved cube, held the way the WCA scrambles (white top, green
+/// front).
+pub fn parse_scramble(scramble: &str) -> Result<Cube, String> {
+    let moves = scramble
+        .split_ascii_whitespace()
+        .map(|token| FullMove::try_from(token).map_err(|e| format!("Bad move: {e}")))
+        .collect::<Result<Vec<FullMove>, String>>()?;
+
+    Ok(Cube::make_solved(Facelet::Green, Facelet::White).apply_many(&moves))
+}
+
+/// How hard a scramble is, roughly
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct ScrambleStats {
+    /// Optimal cross on the easiest color
+    pub cross: usize,
+    /// Optimal cross on white, which is what most people do
+    pub white_cross: usize,
+    /// Length of the solution `solve` finds, tidied up with [canonicalize]; for Kociemba, this
+    /// isn't optimal, but it's close
+    pub solution: usize,
+}
+
+pub fn scramble_stats<Solve: Fn(&Cube) -> Vec<FullMove>>(
+    cube: &Cube,
+    cross: &CrossCache,
+    solve: Solve,
+) -> ScrambleStats {
+    let crosses = solve_all_crosses(cube, cross);
+    let white = crosses
+        .iter()
+        .find(|sol| sol.color == Facelet::White)
+        .expect("Should have a white cross");
+
+    ScrambleStats {
+        cross: best_cross(&crosses).moves.len(),
+        white_cross: white.moves.len(),
+        solution: canonicalize(&solve(cube)).len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::moves::{invert, parse_many};
+    use crate::session::Outcome;
+
+    use super::*;
+
+    const EXPORT: &str = r#"{
+        "session1": [
+            [[0, 12345], "R U R' U'", "", 1700000000],
+            [[2000, 10000], "F2 D' L", "oops", 1700000100],
+            [[-1, 9000, 4000], "B U2", "", 1700000200]
+        ],
+        "session2": [],
+        "session10": [[[0, 1000], "U", "", 1700000300, "extra"]],
+        "properties": {
+            "sessionData": "{\"1\":{\"name\":\"3x3\",\"opt\":{}},\"2\":{\"name\":222,\"opt\":{\"scrType\":\"222so\"}},\"10\":{\"opt\":{\"scrType\":\"333oh\"}}}",
+            "useMilli": true
+        }
+    }"#;
+
+    #[test]
+    fn reads_exports() {
+        let sessions = parse_export(EXPORT).unwrap();
+
+        let names: Vec<&str> = sessions.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["3x3", "222", "10"]);
+
+        // only the 2x2 one says it isn't a 3x3
+        let types: Vec<&str> = sessions.iter().map(|s| s.scramble_type.as_str()).collect();
+        assert_eq!(types, vec!["333", "222so", "333oh"]);
+        let is_3x3: Vec<bool> = sessions.iter().map(CsTimerSession::is_3x3).collect();
+        assert_eq!(is_3x3, vec![true, false, true]);
+
+        let solves = sessions[0].session.solves();
+        assert_eq!(solves.len(), 3);
+        assert_eq!(solves[0].time, Duration::from_millis(12345));
+        assert_eq!(solves[0].scramble, "R U R' U'");
+        assert_eq!(solves[0].date, 1_700_000_000);
+        assert_eq!(solves[1].penalty, Penalty::PlusTwo);
+        assert_eq!(solves[1].outcome(), Outcome::Time(Duration::from_secs(12)));
+        assert_eq!(solves[2].penalty, Penalty::Dnf);
+
+        assert!(sessions[1].session.solves().is_empty());
+        assert_eq!(sessions[2].session.solves().len(), 1);
+
+        let bad = [
+            "[]",
+            "not json",
+            r#"{"session1": 5}"#,
+            r#"{"session1": [[[0], "R", "", 0]]}"#,
+            r#"{"session1": [[[1000, 5000], "R", "", 0]]}"#,
+            r#"{"session1": [[[0, 5000], 7, "", 0]]}"#,
+        ];
+        for text in bad {
+            assert!(parse_export(text).is_err(), "{text}");
+        }
+
+        // errors say where the problem is
+        let err = parse_export(r#"{"session3": [[[0, 1], "R", "", 0], [[5, 1], "R", "", 0]]}"#);
+        assert_eq!(err, Err("session3, solve 2: unknown penalty 5".to_string()));
+    }
+
+    #[test]
+    fn scrambles() {
+        let cube = parse_scramble("R U R' U'").unwrap();
+        assert_eq!(
+            cube,
+            Cube::make_solved(Facelet::Green, Facelet::White).apply_many(&parse_many("R U R' U'"))
+        );
+        assert!(parse_scramble("").unwrap().is_solved());
+
+        for bad in ["R U x", "Rw U", "R3", "M2 U"] {
+            assert!(parse_scramble(bad).is_err(), "{bad}");
+        }
+
+        let cache = CrossCache::initialize();
+
+        // white is on top, so R U R' U' messes up a couple of its cross edges, but the yellow
+        // cross on the bottom isn't touched at all
+        let moves = parse_many("R U R' U'");
+        let cube = parse_scramble("R U R' U'").unwrap();
+        let stats = scramble_stats(&cube, &cache, |_| invert(&moves));
+        assert_eq!(stats.cross, 0);
+        assert!(stats.white_cross > 0);
+        assert_eq!(stats.solution, 4);
+
+        // and the solution gets tidied up before it's counted
+        let stats = scramble_stats(&cube, &cache, |_| parse_many("U U' R2 R2"));
+        assert_eq!(stats.solution, 0);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::moves::Dir;
+
 pub trait FaceletKind {
     fn matches(&self, other: &Self) -> bool;
 }
@@ -18,6 +20,7 @@ pub enum Facelet {
 }
 
 // DO NOT reorder this array; it must be kept in sync with CornerCubelet::to_index
+// This is for the Western color scheme; for other schemes, use ColorScheme::corners
 pub const CORNERS: [[Facelet; 3]; 8] = [
     // top corners first; FUL, FUR, BUR, BUL
     // given from top, clockwise
@@ -34,6 +37,7 @@ pub const CORNERS: [[Facelet; 3]; 8] = [
 ];
 
 // DO NOT reorder this array; it must be kept in sync with SideCubelet::to_index
+// This is for the Western color scheme; for other schemes, use ColorScheme::edges
 pub const EDGES: [[Facelet; 2]; 12] = [
     // left first; UL, FL, DL, BL
     // given from red (correctly oriented, if you put in from the left)
@@ -55,19 +59,148 @@ pub const EDGES: [[Facelet; 2]; 12] = [
     [Facelet::Orange, Facelet::Green],
 ];
 
-pub fn get_third_corner(front: Facelet, top: Facelet) -> Facelet {
-    for corner in CORNERS {
-        for i in 0..3 {
-            if corner[i] == front && corner[(i + 1) % 3] == top {
-                return corner[(i + 2) % 3].clone();
+/// Which color goes on which face of a solved cube. The arrays above (and the comments in them)
+/// are written for the Western scheme held with green in front and yellow on top; any other
+/// scheme is described the same way, by the colors on each face in that reference position.
+///
+/// Every face needs a different color, but otherwise any arrangement is allowed, including
+/// mirror images of the usual schemes.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct ColorScheme {
+    pub u: Facelet,
+    pub d: Facelet,
+    pub f: Facelet,
+    pub b: Facelet,
+    pub l: Facelet,
+    pub r: Facelet,
+}
+
+impl ColorScheme {
+    pub fn new(u: Facelet, d: Facelet, f: Facelet, b: Facelet, l: Facelet, r: Facelet) -> Self {
+        let scheme = Self { u, d, f, b, l, r };
+
+        assert!(
+            scheme.all_different(),
+            "Every face of a color scheme needs a different color"
+        );
+
+        scheme
+    }
+
+    fn all_different(&self) -> bool {
+        let colors = self.colors();
+        (0..6).all(|i| (0..i).all(|j| colors[i] != colors[j]))
+    }
+
+    /// The usual scheme; white opposite yellow, green opposite blue, red opposite orange
+    pub fn western() -> Self {
+        Self {
+            u: Facelet::Yellow,
+            d: Facelet::White,
+            f: Facelet::Green,
+            b: Facelet::Blue,
+            l: Facelet::Red,
+            r: Facelet::Orange,
+        }
+    }
+
+    /// The Western scheme with blue and yellow swapped; white opposite blue, green opposite yellow
+    pub fn japanese() -> Self {
+        Self {
+            u: Facelet::Blue,
+            b: Facelet::Yellow,
+            ..Self::western()
+        }
+    }
+
+    /// Reads the scheme off the centers of the cube, in whatever orientation it's in
+    pub fn from_centers(cube: &Cube) -> Self {
+        Self::new(
+            cube.u.cc.clone(),
+            cube.d.cc.clone(),
+            cube.f.cc.clone(),
+            cube.b.cc.clone(),
+            cube.l.cc.clone(),
+            cube.r.cc.clone(),
+        )
+    }
+
+    /// Colors in the order U D F B L R
+    fn colors(&self) -> [&Facelet; 6] {
+        [&self.u, &self.d, &self.f, &self.b, &self.l, &self.r]
+    }
+
+    /// Color the other scheme has on the face where this scheme has the given color
+    pub fn translate(&self, color: &Facelet, other: &ColorScheme) -> Facelet {
+        let i = self.colors().iter().position(|&c| c == color).unwrap();
+
+        other.colors()[i].clone()
+    }
+
+    /// Same as [CORNERS], but for this scheme
+    pub fn corners(&self) -> [[Facelet; 3]; 8] {
+        let western = Self::western();
+        CORNERS.map(|corner| corner.map(|f| western.translate(&f, self)))
+    }
+
+    /// Same as [EDGES], but for this scheme
+    pub fn edges(&self) -> [[Facelet; 2]; 12] {
+        let western = Self::western();
+        EDGES.map(|edge| edge.map(|f| western.translate(&f, self)))
+    }
+
+    /// Given the colors on the front and top of some corner, gives the third color, which is
+    /// the one going clockwise from the top
+    pub fn third_corner(&self, front: &Facelet, top: &Facelet) -> Facelet {
+        for corner in self.corners() {
+            for i in 0..3 {
+                if &corner[i] == front && &corner[(i + 1) % 3] == top {
+                    return corner[(i + 2) % 3].clone();
+                }
             }
         }
+
+        panic!(
+            "There is no corner with front {:?} and top {:?}",
+            front, top
+        );
     }
+}
+
+impl<'a> TryFrom<&'a str> for ColorScheme {
+    type Error = &'a str;
+
+    /// Parses `western`, `japanese`, or six color letters (from Y W G B R O) giving the colors of
+    /// the U D F B L R faces, in that order; so `YWGBRO` is the Western scheme.
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value {
+            "western" => return Ok(Self::western()),
+            "japanese" => return Ok(Self::japanese()),
+            _ => {}
+        }
+
+        let colors = value
+            .chars()
+            .map(|c| match c {
+                'Y' => Ok(Facelet::Yellow),
+                'W' => Ok(Facelet::White),
+                'G' => Ok(Facelet::Green),
+                'B' => Ok(Facelet::Blue),
+                'R' => Ok(Facelet::Red),
+                'O' => Ok(Facelet::Orange),
+                _ => Err(value),
+            })
+            .collect::<Result<Vec<Facelet>, _>>()?;
 
-    panic!(
-        "There is no corner with front {:?} and top {:?}",
-        front, top
-    );
+        let [u, d, f, b, l, r]: [Facelet; 6] = colors.try_into().map_err(|_| value)?;
+        let scheme = Self { u, d, f, b, l, r };
+
+        if scheme.all_different() {
+            Ok(scheme)
+        } else {
+            Err(value)
+        }
+    }
 }
 
 impl FaceletKind for Facelet {
@@ -238,11 +371,17 @@ pub struct Cube<FaceletType = Facelet> {
 }
 
 impl Cube<Facelet> {
+    /// Solved cube in the Western color scheme, held with the given colors in front and on top
     pub fn make_solved(front: Facelet, top: Facelet) -> Self {
-        let right = get_third_corner(front.clone(), top.clone());
-        let down = get_third_corner(front.clone(), right.clone());
-        let left = get_third_corner(front.clone(), down.clone());
-        let back = get_third_corner(right.clone(), top.clone());
+        Self::make_solved_with(&ColorScheme::western(), front, top)
+    }
+
+    /// Solved cube in the given color scheme, held with the given colors in front and on top
+    pub fn make_solved_with(scheme: &ColorScheme, front: Facelet, top: Facelet) -> Self {
+        let right = scheme.third_corner(&front, &top);
+        let down = scheme.third_corner(&front, &right);
+        let left = scheme.third_corner(&front, &down);
+        let back = scheme.third_corner(&right, &top);
 
         Self {
             u: UDFace::make_solved(top),
@@ -253,6 +392,15 @@ impl Cube<Facelet> {
             b: FBFace::make_solved(back),
         }
     }
+
+    /// Repaints the cube so its centers match the given scheme, as it's described; that is,
+    /// the U center gets the scheme's U color, and so on, and every other facelet gets the color
+    /// of the center it belongs with. This is handy for comparing cubes held in different ways.
+    pub fn recolored(&self, scheme: &ColorScheme) -> Self {
+        let current = ColorScheme::from_centers(self);
+
+        Cube::from_fn(|face, row, col| current.translate(self.face_grid(face)[row][col], scheme))
+    }
 }
 
 impl<F: FaceletKind> Cube<F> {
@@ -265,3 +413,242 @@ impl<F: FaceletKind> Cube<F> {
             && self.r.is_solved()
     }
 }
+
+impl<F> Cube<F> {
+    /// Gives the facelets of a face as a 3x3 grid, as seen when looking straight at that face
+    /// on the usual unfolded net. That is, the side faces are upright, the U face has its B edge
+    /// at the top, and the D face has its F edge at the top.
+    pub fn face_grid(&self, face: Dir) -> [[&F; 3]; 3] {
+        match face {
+            Dir::U => {
+                let u = &self.u;
+                [
+                    [&u.bl, &u.bc, &u.br],
+                    [&u.lc, &u.cc, &u.rc],
+                    [&u.fl, &u.fc, &u.fr],
+                ]
+            }
+            Dir::D => {
+                let d = &self.d;
+                [
+                    [&d.fl, &d.fc, &d.fr],
+                    [&d.lc, &d.cc, &d.rc],
+                    [&d.bl, &d.bc, &d.br],
+                ]
+            }
+            Dir::F => {
+                let f = &self.f;
+                [
+                    [&f.ul, &f.uc, &f.ur],
+                    [&f.lc, &f.cc, &f.rc],
+                    [&f.dl, &f.dc, &f.dr],
+                ]
+            }
+            Dir::B => {
+                // seen from behind, so left and right are swapped
+                let b = &self.b;
+                [
+                    [&b.ur, &b.uc, &b.ul],
+                    [&b.rc, &b.cc, &b.lc],
+                    [&b.dr, &b.dc, &b.dl],
+                ]
+            }
+            Dir::L => {
+                let l = &self.l;
+                [
+                    [&l.ub, &l.uc, &l.uf],
+                    [&l.bc, &l.cc, &l.fc],
+                    [&l.db, &l.dc, &l.df],
+                ]
+            }
+            Dir::R => {
+                let r = &self.r;
+                [
+                    [&r.uf, &r.uc, &r.ub],
+                    [&r.fc, &r.cc, &r.bc],
+                    [&r.df, &r.dc, &r.db],
+                ]
+            }
+        }
+    }
+
+    /// Builds a cube one facelet at a time. The closure is given the face, row and column of
+    /// each facelet, in the same coordinates as [Cube::face_grid].
+    pub fn from_fn<Func: FnMut(Dir, usize, usize) -> F>(mut f: Func) -> Self {
+        Self {
+            u: UDFace {
+                bl: f(Dir::U, 0, 0),
+                bc: f(Dir::U, 0, 1),
+                br: f(Dir::U, 0, 2),
+                lc: f(Dir::U, 1, 0),
+                cc: f(Dir::U, 1, 1),
+                rc: f(Dir::U, 1, 2),
+                fl: f(Dir::U, 2, 0),
+                fc: f(Dir::U, 2, 1),
+                fr: f(Dir::U, 2, 2),
+            },
+            d: UDFace {
+                fl: f(Dir::D, 0, 0),
+                fc: f(Dir::D, 0, 1),
+                fr: f(Dir::D, 0, 2),
+                lc: f(Dir::D, 1, 0),
+                cc: f(Dir::D, 1, 1),
+                rc: f(Dir::D, 1, 2),
+                bl: f(Dir::D, 2, 0),
+                bc: f(Dir::D, 2, 1),
+                br: f(Dir::D, 2, 2),
+            },
+            f: FBFace {
+                ul: f(Dir::F, 0, 0),
+                uc: f(Dir::F, 0, 1),
+                ur: f(Dir::F, 0, 2),
+                lc: f(Dir::F, 1, 0),
+                cc: f(Dir::F, 1, 1),
+                rc: f(Dir::F, 1, 2),
+                dl: f(Dir::F, 2, 0),
+                dc: f(Dir::F, 2, 1),
+                dr: f(Dir::F, 2, 2),
+            },
+            b: FBFace {
+                ur: f(Dir::B, 0, 0),
+                uc: f(Dir::B, 0, 1),
+                ul: f(Dir::B, 0, 2),
+                rc: f(Dir::B, 1, 0),
+                cc: f(Dir::B, 1, 1),
+                lc: f(Dir::B, 1, 2),
+                dr: f(Dir::B, 2, 0),
+                dc: f(Dir::B, 2, 1),
+                dl: f(Dir::B, 2, 2),
+            },
+            l: LRFace {
+                ub: f(Dir::L, 0, 0),
+                uc: f(Dir::L, 0, 1),
+                uf: f(Dir::L, 0, 2),
+                bc: f(Dir::L, 1, 0),
+                cc: f(Dir::L, 1, 1),
+                fc: f(Dir::L, 1, 2),
+                db: f(Dir::L, 2, 0),
+                dc: f(Dir::L, 2, 1),
+                df: f(Dir::L, 2, 2),
+            },
+            r: LRFace {
+                uf: f(Dir::R, 0, 0),
+                uc: f(Dir::R, 0, 1),
+                ub: f(Dir::R, 0, 2),
+                fc: f(Dir::R, 1, 0),
+                cc: f(Dir::R, 1, 1),
+                bc: f(Dir::R, 1, 2),
+                df: f(Dir::R, 2, 0),
+                dc: f(Dir::R, 2, 1),
+                db: f(Dir::R, 2, 2),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::corner_orientation_state::CornerOrientationState;
+    use crate::edge_orientation_state::EdgeOrientationState;
+    use crate::moves::{parse_many, ApplyMove};
+    use crate::total_position_state::CubePositions;
+
+    use super::*;
+
+    #[test]
+    fn western_matches_old_arrays() {
+        let western = ColorScheme::western();
+
+        assert_eq!(western.corners(), CORNERS);
+        assert_eq!(western.edges(), EDGES);
+        assert_eq!(
+            Cube::make_solved_with(&western, Facelet::Green, Facelet::White),
+            Cube::make_solved(Facelet::Green, Facelet::White)
+        );
+        assert_eq!(
+            ColorScheme::from_centers(&Cube::make_solved(Facelet::Green, Facelet::Yellow)),
+            western
+        );
+    }
+
+    #[test]
+    fn japanese_scheme() {
+        let japanese = ColorScheme::japanese();
+
+        // white top, green front; blue is opposite white, and yellow is opposite green
+        let cube = Cube::make_solved_with(&japanese, Facelet::Green, Facelet::White);
+        assert!(cube.is_solved());
+        assert_eq!(cube.d.cc, Facelet::Blue);
+        assert_eq!(cube.b.cc, Facelet::Yellow);
+        assert_eq!(cube.r.cc, Facelet::Red);
+
+        // every orientation is available
+        for (front, top) in [
+            (Facelet::Yellow, Facelet::Orange),
+            (Facelet::Blue, Facelet::Green),
+            (Facelet::Red, Facelet::Blue),
+        ] {
+            let cube = Cube::make_solved_with(&japanese, front.clone(), top.clone());
+            assert_eq!(cube.f.cc, front);
+            assert_eq!(cube.u.cc, top);
+        }
+    }
+
+    #[test]
+    fn projections_ignore_scheme() {
+        let moves = parse_many("B U F' L U R' L' F2 D' F2 L F' R' D L' D U2 R' U2 F' D' R2 F2 B'");
+
+        // a made up scheme, which is the mirror image of the Western one
+        let mirrored = ColorScheme::new(
+            Facelet::Yellow,
+            Facelet::White,
+            Facelet::Green,
+            Facelet::Blue,
+            Facelet::Orange,
+            Facelet::Red,
+        );
+
+        let western = ColorScheme::western();
+
+        for scheme in [ColorScheme::japanese(), mirrored] {
+            let a = Cube::make_solved_with(&western, western.f.clone(), western.u.clone())
+                .apply_many(&moves);
+            let b = Cube::make_solved_with(&scheme, scheme.f.clone(), scheme.u.clone())
+                .apply_many(&moves);
+
+            assert_eq!(CubePositions::from_cube(&a), CubePositions::from_cube(&b));
+            assert_eq!(
+                CornerOrientationState::from_cube(&a),
+                CornerOrientationState::from_cube(&b)
+            );
+            assert_eq!(
+                EdgeOrientationState::from_cube(&a),
+                EdgeOrientationState::from_cube(&b)
+            );
+        }
+    }
+
+    #[test]
+    fn parse_schemes() {
+        assert_eq!(ColorScheme::try_from("western"), Ok(ColorScheme::western()));
+        assert_eq!(ColorScheme::try_from("YWGBRO"), Ok(ColorScheme::western()));
+        assert_eq!(ColorScheme::try_from("BWGYRO"), Ok(ColorScheme::japanese()));
+
+        assert!(ColorScheme::try_from("YWGBR").is_err());
+        assert!(ColorScheme::try_from("YYGBRO").is_err());
+        assert!(ColorScheme::try_from("YWGBRP").is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn scheme_needs_six_colors() {
+        ColorScheme::new(
+            Facelet::Yellow,
+            Facelet::Yellow,
+            Facelet::Green,
+            Facelet::Blue,
+            Facelet::Red,
+            Facelet::Orange,
+        );
+    }
+}
@@ -3,7 +3,9 @@ use crate::moves::{Amt, ApplyMove, CanMove, Dir, FullMove};
 
 const ALL_AMTS: [Amt; 3] = [Amt::One, Amt::Two, Amt::Rev];
 
-fn can_follow(last: Option<Dir>, next: Dir) -> bool {
+/// Whether `next` is worth doing right after `last`; the solvers never turn the same face twice
+/// in a row, and only turn opposite faces in one order
+pub fn can_follow(last: Option<Dir>, next: Dir) -> bool {
     if last.is_none() {
         return true;
     }
@@ -12,17 +14,10 @@ fn can_follow(last: Option<Dir>, next: Dir) -> bool {
 
     // can't repeat a direction, and if two directions commute, have to pick an order
     // so with no significance -- B before F, L before R, D before U
-    if last == next {
-        false
-    } else if last == Dir::F && next == Dir::B {
-        false
-    } else if last == Dir::R && next == Dir::L {
-        false
-    } else if last == Dir::U && next == Dir::D {
-        false
-    } else {
-        true
-    }
+    !(last == next
+        || (last == Dir::F && next == Dir::B)
+        || (last == Dir::R && next == Dir::L)
+        || (last == Dir::U && next == Dir::D))
 }
 
 pub fn solve<
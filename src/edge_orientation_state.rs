@@ -1,5 +1,6 @@
 use crate::cube::{Cube, Facelet};
-use crate::moves::CanMove;
+use crate::moves::{Amt, CanMove, Dir};
+use crate::transform::{Axis, Rotation};
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub struct EdgeOrientationState {
@@ -45,15 +46,14 @@ impl EdgeOrientationState {
         //      F/B color it's bad
         //      U/D color you need to look at the side of the edge. If the side is L/R it's bad.
         let lr_good = |lr: &Facelet, other: &Facelet| {
-            (!is_fb_color(lr)) && !(is_ud_color(lr) && is_lr_color(other))
+            !(is_fb_color(lr) || is_ud_color(lr) && is_lr_color(other))
         };
 
         // Then look at the U/D edges on the mid slice. If you see:
         //      F/B color it's bad
         //      U/D color you need to look at the side of the edge. If the side is L/R it's bad.
-        let ud_mid_good = |ud: &Facelet, fb: &Facelet| {
-            (!is_fb_color(ud)) && !(is_ud_color(ud) && is_lr_color(fb))
-        };
+        let ud_mid_good =
+            |ud: &Facelet, fb: &Facelet| !(is_fb_color(ud) || is_ud_color(ud) && is_lr_color(fb));
 
         Self {
             // ud mid edges ...
@@ -92,6 +92,26 @@ impl EdgeOrientationState {
         }
     }
 
+    /// Gets whether the cubelet in the given slot is good, where slots are numbered as in
+    /// [crate::edge_position_state::SideCubelet::to_index]
+    pub fn ind(&self, index: u8) -> bool {
+        match index {
+            0 => self.ul,
+            1 => self.fl,
+            2 => self.dl,
+            3 => self.bl,
+            4 => self.uf,
+            5 => self.df,
+            6 => self.db,
+            7 => self.ub,
+            8 => self.ur,
+            9 => self.br,
+            10 => self.dr,
+            11 => self.fr,
+            _ => panic!("Out of range index {index}"),
+        }
+    }
+
     pub fn is_solvable(&self) -> bool {
         // solvable if an even number of pieces are out of orientation
         let is_flipped = self.uf
@@ -190,6 +210,143 @@ impl CanMove for EdgeOrientationState {
     }
 }
 
+/// Edge orientation relative to any axis. An edge is good if it can be solved without quarter
+/// turns of the two faces at the ends of the axis; so [EdgeOrientationState] is the special case
+/// of the Y (U/D) axis, as in Thistlethwaite, and ZZ uses the Z (F/B) axis.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct AxisEdgeOrientationState {
+    pub axis: Axis,
+    pub edges: EdgeOrientationState,
+}
+
+impl AxisEdgeOrientationState {
+    pub fn from_cube(cube: &Cube, axis: Axis) -> Self {
+        // turn the cube so the axis is U/D, work it out there, then put each edge back where
+        // it was before the turn
+        let edges = match axis {
+            Axis::Y => EdgeOrientationState::from_cube(cube),
+            Axis::Z => {
+                // x takes F up to U
+                let e = EdgeOrientationState::from_cube(&cube.rotated(Rotation {
+                    axis: Axis::X,
+                    amt: Amt::One,
+                }));
+
+                EdgeOrientationState {
+                    uf: e.ub,
+                    ub: e.db,
+                    db: e.df,
+                    df: e.uf,
+                    ul: e.bl,
+                    bl: e.dl,
+                    dl: e.fl,
+                    fl: e.ul,
+                    ur: e.br,
+                    br: e.dr,
+                    dr: e.fr,
+                    fr: e.ur,
+                }
+            }
+            Axis::X => {
+                // z takes L up to U
+                let e = EdgeOrientationState::from_cube(&cube.rotated(Rotation {
+                    axis: Axis::Z,
+                    amt: Amt::One,
+                }));
+
+                EdgeOrientationState {
+                    uf: e.fr,
+                    fr: e.df,
+                    df: e.fl,
+                    fl: e.uf,
+                    ub: e.br,
+                    br: e.db,
+                    db: e.bl,
+                    bl: e.ub,
+                    ul: e.ur,
+                    ur: e.dr,
+                    dr: e.dl,
+                    dl: e.ul,
+                }
+            }
+        };
+
+        Self { axis, edges }
+    }
+
+    pub fn make_solved(axis: Axis) -> Self {
+        Self {
+            axis,
+            edges: EdgeOrientationState::make_solved(),
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.edges.is_solved()
+    }
+
+    /// Number of edges which are out of orientation
+    pub fn bad_count(&self) -> usize {
+        (0..12).filter(|&i| !self.edges.ind(i)).count()
+    }
+
+    /// Does a move of the given face. [EdgeOrientationState] already moves the edges around,
+    /// and flips the ones on U and D; this fixes that up for the axis we actually care about.
+    fn turn(self, dir: Dir, moved: EdgeOrientationState) -> Self {
+        let on_axis = match self.axis {
+            Axis::X => matches!(dir, Dir::L | Dir::R),
+            Axis::Y => matches!(dir, Dir::U | Dir::D),
+            Axis::Z => matches!(dir, Dir::F | Dir::B),
+        };
+        let flipped = matches!(dir, Dir::U | Dir::D);
+
+        let mut e = moved;
+
+        if on_axis != flipped {
+            let face = match dir {
+                Dir::U => [&mut e.uf, &mut e.ub, &mut e.ul, &mut e.ur],
+                Dir::D => [&mut e.df, &mut e.db, &mut e.dl, &mut e.dr],
+                Dir::F => [&mut e.uf, &mut e.df, &mut e.fl, &mut e.fr],
+                Dir::B => [&mut e.ub, &mut e.db, &mut e.bl, &mut e.br],
+                Dir::L => [&mut e.ul, &mut e.dl, &mut e.fl, &mut e.bl],
+                Dir::R => [&mut e.ur, &mut e.dr, &mut e.fr, &mut e.br],
+            };
+
+            for good in face {
+                *good = !*good;
+            }
+        }
+
+        Self { edges: e, ..self }
+    }
+}
+
+impl CanMove for AxisEdgeOrientationState {
+    fn r(self) -> Self {
+        self.turn(Dir::R, self.edges.r())
+    }
+
+    fn l(self) -> Self {
+        self.turn(Dir::L, self.edges.l())
+    }
+
+    fn u(self) -> Self {
+        self.turn(Dir::U, self.edges.u())
+    }
+
+    fn d(self) -> Self {
+        self.turn(Dir::D, self.edges.d())
+    }
+
+    fn b(self) -> Self {
+        self.turn(Dir::B, self.edges.b())
+    }
+
+    fn f(self) -> Self {
+        self.turn(Dir::F, self.edges.f())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -230,4 +387,50 @@ mod tests {
 
         assert!(!input.is_solvable());
     }
+
+    #[test]
+    fn any_axis() {
+        use crate::moves::{parse_many, ApplyMove, ALL_DIRS};
+
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+        let scramble = parse_many("R U2 F' L D2 B R' U F2 L' D B2 U'");
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            for dir in ALL_DIRS {
+                let cube = solved
+                    .clone()
+                    .apply(crate::moves::FullMove { dir, amt: Amt::One });
+                let state = AxisEdgeOrientationState::from_cube(&cube, axis);
+
+                // only quarter turns of the faces on the axis flip edges
+                let on_axis = AxisEdgeOrientationState::make_solved(axis)
+                    .apply(crate::moves::FullMove { dir, amt: Amt::One });
+                assert_eq!(state, on_axis, "{dir:?} on {axis:?}");
+                assert_eq!(state.bad_count() == 4, on_axis.bad_count() == 4);
+            }
+
+            // and moving the state along matches working it out from the cube
+            let state = AxisEdgeOrientationState::make_solved(axis).apply_many(&scramble);
+            let cube = solved.clone().apply_many(&scramble);
+            assert_eq!(state, AxisEdgeOrientationState::from_cube(&cube, axis));
+        }
+
+        // the U/D axis is the usual one
+        let cube = solved.apply_many(&scramble);
+        assert_eq!(
+            AxisEdgeOrientationState::from_cube(&cube, Axis::Y).edges,
+            EdgeOrientationState::from_cube(&cube)
+        );
+
+        let flipped =
+            Cube::make_solved(Facelet::Green, Facelet::Yellow).apply_many(&parse_many("F"));
+        assert_eq!(
+            AxisEdgeOrientationState::from_cube(&flipped, Axis::Z).bad_count(),
+            4
+        );
+        assert_eq!(
+            AxisEdgeOrientationState::from_cube(&flipped, Axis::Y).bad_count(),
+            0
+        );
+    }
 }
@@ -78,7 +78,8 @@ impl CubeEdgePositions {
         }
     }
 
-    fn ind(&self, index: u8) -> SideCubelet {
+    /// Gets the cubelet in the given slot, where slots are numbered as in [SideCubelet::to_index]
+    pub fn ind(&self, index: u8) -> SideCubelet {
         match index {
             0 => self.ul.clone(),
             1 => self.fl.clone(),
@@ -240,10 +241,10 @@ impl CanMove for CubeEdgePositions {
 
     fn b(self) -> Self {
         CubeEdgePositions {
-            ub: self.bl,
-            bl: self.db,
-            db: self.br,
-            br: self.ub,
+            ub: self.br,
+            br: self.db,
+            db: self.bl,
+            bl: self.ub,
             ..self
         }
     }
@@ -262,6 +263,7 @@ impl CanMove for CubeEdgePositions {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::moves::{parse_many, ApplyMove};
 
     #[test]
     fn solved_test() {
@@ -312,4 +314,16 @@ mod tests {
 
         assert!(!input.directly_solvable());
     }
+
+    #[test]
+    fn moves_match_cube() {
+        let moves = parse_many("B U F' L U R' L' F2 D' F2 L F' R' D L' D U2 R' U2 F' D' R2 F2 B'");
+
+        for i in 0..=moves.len() {
+            let cube = Cube::make_solved(Facelet::Green, Facelet::Yellow).apply_many(&moves[..i]);
+            let state = CubeEdgePositions::make_solved().apply_many(&moves[..i]);
+
+            assert_eq!(CubeEdgePositions::from_cube(&cube), state);
+        }
+    }
 }
@@ -0,0 +1,230 @@
+//! Module for solving cubes which were scrambled with only a few faces: <R, U> (2-gen) and
+//! <R, U, F> (3-gen). Every solution here only turns those faces, so undoing one gives a scramble
+//! which stays in the subset.
+//!
+//! 2-gen is small enough to solve optimally, with one table for its corners and one for its
+//! edges. 3-gen isn't, so it's done in two phases, a bit like Kociemba: first get into <R, U>
+//! using R, U and F, and then solve that as 2-gen. A 3-gen cube is in <R, U> exactly when DFL,
+//! DF and FL are solved, the edges are oriented (for F and B, since R and U can't flip them), and
+//! the corners are in one of the 120 arrangements that R and U can make.
+
+use ahash::HashSet;
+
+use crate::corner_position_state::CubeCornerPositions;
+use crate::cube::{AmbigFacelet, ColorScheme, Cube, Facelet};
+use crate::dfs_util;
+use crate::edge_orientation_state::AxisEdgeOrientationState;
+use crate::heuristic_caches::{Heuristic, HeuristicCache};
+use crate::moves::{Amt, ApplyMove, CanMove, Dir, FullMove};
+use crate::shadow::MaskSpec;
+use crate::solve::{solve_mask, MaskCache};
+use crate::transform::Axis;
+
+pub const TWO_GEN: [Dir; 2] = [Dir::R, Dir::U];
+pub const THREE_GEN: [Dir; 3] = [Dir::R, Dir::U, Dir::F];
+
+// comfortably more than any <R, U> state needs
+const MAX_TWO_GEN_MOVES: usize = 25;
+
+// getting into <R, U> seems to take 10 or so; this is just so a bug doesn't run forever
+const MAX_TO_TWO_GEN_MOVES: usize = 20;
+
+pub struct GeneratorCaches {
+    two_gen: MaskCache,
+    to_two_gen: ToTwoGenCache,
+}
+
+impl GeneratorCaches {
+    pub fn initialize() -> Self {
+        let parts = [
+            "solved: UFL UFR UBL UBR DFR DBR",
+            "solved: UF UR UB UL FR BR DR",
+        ];
+        let parts = parts
+            .into_iter()
+            .map(|part| MaskSpec::try_from(part).unwrap())
+            .collect();
+
+        Self {
+            two_gen: MaskCache::from_parts(parts, &TWO_GEN),
+            to_two_gen: ToTwoGenCache::initialize(),
+        }
+    }
+}
+
+/// Optimal solution for a cube in <R, U>, only turning R and U. Panics if the cube isn't in
+/// <R, U>.
+pub fn solve_two_gen(cube: &Cube, caches: &GeneratorCaches) -> Vec<FullMove> {
+    solve_mask(cube, &caches.two_gen, MAX_TWO_GEN_MOVES)
+}
+
+/// Solution for a cube in <R, U, F>, only turning R, U and F; not optimal, but not far off. Panics
+/// if the cube isn't in <R, U, F>.
+pub fn solve_three_gen(cube: &Cube, caches: &GeneratorCaches) -> Vec<FullMove> {
+    let mut moves = solve_to_two_gen(cube, &caches.to_two_gen);
+    let two_gen = cube.clone().apply_many(&moves);
+    moves.extend(solve_two_gen(&two_gen, caches));
+    moves
+}
+
+fn solve_to_two_gen(cube: &Cube, cache: &ToTwoGenCache) -> Vec<FullMove> {
+    dfs_util::solve(
+        ToTwoGenState::from_cube(cube, cache),
+        &THREE_GEN,
+        &[],
+        |s| cache.is_solved(s),
+        cache,
+        MAX_TO_TWO_GEN_MOVES + 1,
+    )
+}
+
+struct ToTwoGenCache {
+    /// DFL, DF and FL, which R and U don't touch
+    outside: MaskSpec,
+    outside_cache: HeuristicCache<Cube<AmbigFacelet>>,
+    edges_cache: HeuristicCache<AxisEdgeOrientationState>,
+    /// Where the corners can be, using only R and U
+    two_gen_corners: HashSet<CubeCornerPositions>,
+    corners_cache: HeuristicCache<CubeCornerPositions>,
+}
+
+impl ToTwoGenCache {
+    fn initialize() -> Self {
+        let outside = MaskSpec::try_from("solved: DFL DF FL").unwrap();
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+
+        // there are only 120 of these, so just find them all
+        let mut two_gen_corners = HashSet::default();
+        let mut to_visit = vec![CubeCornerPositions::make_solved()];
+        while let Some(corners) = to_visit.pop() {
+            if two_gen_corners.insert(corners.clone()) {
+                for dir in TWO_GEN {
+                    to_visit.push(corners.clone().apply(FullMove { dir, amt: Amt::One }));
+                }
+            }
+        }
+
+        Self {
+            outside_cache: HeuristicCache::from_goal(outside.apply(solved), &THREE_GEN, &[]),
+            outside,
+            edges_cache: HeuristicCache::from_goal(
+                AxisEdgeOrientationState::make_solved(Axis::Z),
+                &THREE_GEN,
+                &[],
+            ),
+            corners_cache: HeuristicCache::from_set(&two_gen_corners, &THREE_GEN, &[]),
+            two_gen_corners,
+        }
+    }
+
+    fn is_solved(&self, s: &ToTwoGenState) -> bool {
+        s.outside.is_solved() && s.edges.is_solved() && self.two_gen_corners.contains(&s.corners)
+    }
+}
+
+impl Heuristic<ToTwoGenState> for ToTwoGenCache {
+    fn evaluate(&self, s: &ToTwoGenState) -> usize {
+        let outside = self.outside_cache.evaluate(&s.outside);
+        let edges = self.edges_cache.evaluate(&s.edges);
+        let corners = self.corners_cache.evaluate(&s.corners);
+
+        outside.max(edges).max(corners)
+    }
+}
+
+#[derive(Clone, Hash, Eq, PartialEq, Debug)]
+struct ToTwoGenState {
+    outside: Cube<AmbigFacelet>,
+    edges: AxisEdgeOrientationState,
+    corners: CubeCornerPositions,
+}
+
+impl ToTwoGenState {
+    fn from_cube(cube: &Cube, cache: &ToTwoGenCache) -> Self {
+        Self {
+            outside: cache.outside.apply(cube.recolored(&ColorScheme::western())),
+            edges: AxisEdgeOrientationState::from_cube(cube, Axis::Z),
+            corners: CubeCornerPositions::from_cube(cube),
+        }
+    }
+}
+
+impl CanMove for ToTwoGenState {
+    fn r(self) -> Self {
+        Self {
+            outside: self.outside.r(),
+            edges: self.edges.r(),
+            corners: self.corners.r(),
+        }
+    }
+
+    fn l(self) -> Self {
+        Self {
+            outside: self.outside.l(),
+            edges: self.edges.l(),
+            corners: self.corners.l(),
+        }
+    }
+
+    fn u(self) -> Self {
+        Self {
+            outside: self.outside.u(),
+            edges: self.edges.u(),
+            corners: self.corners.u(),
+        }
+    }
+
+    fn d(self) -> Self {
+        Self {
+            outside: self.outside.d(),
+            edges: self.edges.d(),
+            corners: self.corners.d(),
+        }
+    }
+
+    fn b(self) -> Self {
+        Self {
+            outside: self.outside.b(),
+            edges: self.edges.b(),
+            corners: self.corners.b(),
+        }
+    }
+
+    fn f(self) -> Self {
+        Self {
+            outside: self.outside.f(),
+            edges: self.edges.f(),
+            corners: self.corners.f(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::scramble::{scramble_subset, Subset};
+
+    use super::*;
+
+    #[test]
+    fn only_own_faces() {
+        let caches = GeneratorCaches::initialize();
+        let scheme = ColorScheme::western();
+
+        for seed in 0..5 {
+            let mut rng = StdRng::seed_from_u64(seed);
+
+            let cube = scramble_subset(&scheme, Subset::TwoGen, &mut rng);
+            let moves = solve_two_gen(&cube, &caches);
+            assert!(cube.clone().apply_many(&moves).is_solved());
+            assert!(moves.iter().all(|fm| TWO_GEN.contains(&fm.dir)));
+
+            let cube = scramble_subset(&scheme, Subset::ThreeGen, &mut rng);
+            let moves = solve_three_gen(&cube, &caches);
+            assert!(cube.clone().apply_many(&moves).is_solved());
+            assert!(moves.iter().all(|fm| THREE_GEN.contains(&fm.dir)));
+        }
+    }
+}
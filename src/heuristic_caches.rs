@@ -150,7 +150,7 @@ pub trait Heuristic<StateType> {
 
 impl<StateType: Hash + Eq + PartialEq> Heuristic<StateType> for HeuristicCache<StateType> {
     fn evaluate(&self, state: &StateType) -> usize {
-        if let Some(&cost) = self.known_costs.get(&state) {
+        if let Some(&cost) = self.known_costs.get(state) {
             return cost;
         }
 
@@ -27,8 +27,12 @@
 pub use h0h1::{solve_to_h1, H0toH1Cache};
 pub use h1h2::{solve_to_h2, H1toH2Cache};
 
+use crate::corner_orientation_state::CornerOrientationState;
 use crate::cube::Cube;
+use crate::edge_orientation_state::EdgeOrientationState;
+use crate::edge_slice_state::EdgeMidSliceState;
 use crate::moves::{ApplyMove, FullMove};
+use crate::playback::Phase;
 
 mod h0h1;
 mod h1h2;
@@ -58,3 +62,31 @@ pub fn full_solve(cube: &Cube, cache: &KociembaCaches) -> Vec<FullMove> {
     full_solution.extend(g2_solution);
     full_solution
 }
+
+/// Same as [full_solve], but split up by phase, with what each phase achieved. Checks each
+/// invariant really does hold once its phase is done.
+pub fn solve_in_phases(cube: &Cube, cache: &KociembaCaches) -> Vec<Phase> {
+    let h1_solution = solve_to_h1(cube, &cache.h0h1cache);
+    let h1_solved = cube.clone().apply_many(&h1_solution);
+    assert!(EdgeOrientationState::from_cube(&h1_solved).is_solved());
+    assert!(CornerOrientationState::from_cube(&h1_solved).is_solved());
+    assert!(EdgeMidSliceState::from_cube(&h1_solved).is_solved());
+
+    let h2_solution = solve_to_h2(&h1_solved, &cache.h1h2cache);
+    assert!(h1_solved.apply_many(&h2_solution).is_solved());
+
+    vec![
+        Phase {
+            reached: "H1".to_string(),
+            invariant: "Every edge and corner is oriented and the M slice edges are in the M \
+                        slice, so only L, R and half turns are needed from here"
+                .to_string(),
+            moves: h1_solution,
+        },
+        Phase {
+            reached: "H2".to_string(),
+            invariant: "Solved".to_string(),
+            moves: h2_solution,
+        },
+    ]
+}
@@ -76,11 +76,9 @@ impl Heuristic<RunningState> for H0toH1Cache {
         let edges = self.edge_orientation.evaluate(&s.edge_or);
         let corners = self.corner_orientation.evaluate(&s.corner_or);
 
-        // TODO: can probably improve this so we don't need to do the clones?
-        let total_state = self.total_state.evaluate(&(
-            s.edge_or.clone(),
-            (s.corner_or.clone(), s.mid_slice.clone()),
-        ));
+        let total_state = self
+            .total_state
+            .evaluate(&(s.edge_or, (s.corner_or, s.mid_slice)));
 
         slice.max(edges).max(corners).max(total_state)
     }
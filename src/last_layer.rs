@@ -0,0 +1,395 @@
+//! Module for the last layer of CFOP; that is, the algorithms for OLL (orienting the last layer)
+//! and PLL (permuting it). These are written in the usual notation, so they need to go through
+//! [crate::transform::parse_alg] to get face turns out of them.
+//!
+//! There's also recognition: given a cube with the first two layers solved, which case is it in,
+//! and how does the U face need to turn first? This is done by brute force, trying every
+//! algorithm after every AUF, which is plenty fast since there are only a few hundred options.
+
+use std::fmt::{Display, Formatter};
+
+use crate::cube::Cube;
+use crate::moves::{Amt, ApplyMove, Dir, FullMove};
+use crate::shadow::{to_cross_and_pairs, to_oll, ALL_SLOTS};
+use crate::transform::parse_alg;
+
+/// Every OLL algorithm, along with the usual name of its shape; the first entry is OLL 1, and so
+/// on. Lots of cases share a shape, so the number is what tells them apart
+pub const OLL_ALGS: [(&str, &str); 57] = [
+    ("Dot", "R U2 R2 F R F' U2 R' F R F'"),
+    ("Dot", "F R U R' U' F' f R U R' U' f'"),
+    ("Dot", "f R U R' U' f' U' F R U R' U' F'"),
+    ("Dot", "f R U R' U' f' U F R U R' U' F'"),
+    ("Square", "r' U2 R U R' U r"),
+    ("Square", "r U2 R' U' R U' r'"),
+    ("Lightning", "r U R' U R U2 r'"),
+    ("Lightning", "l' U' L U' L' U2 l"),
+    ("Fish", "R U R' U' R' F R2 U R' U' F'"),
+    ("Fish", "R U R' U R' F R F' R U2 R'"),
+    ("Lightning", "r U R' U R' F R F' R U2 r'"),
+    ("Lightning", "M' R' U' R U' R' U2 R U' R r'"),
+    ("Knight", "F U R U' R2 F' R U R U' R'"),
+    ("Knight", "R' F R U R' F' R F U' F'"),
+    ("Knight", "r' U' r R' U' R U r' U r"),
+    ("Knight", "r U r' R U R' U' r U' r'"),
+    ("Dot", "F R' F' R2 r' U R U' R' U' M'"),
+    ("Dot", "r U R' U R U2 r2 U' R U' R' U2 r"),
+    ("Dot", "r' R U R U R' U' M' R' F R F'"),
+    ("Dot", "r U R' U' M2 U R U' R' U' M'"),
+    ("H", "R U2 R' U' R U R' U' R U' R'"),
+    ("Pi", "R U2 R2 U' R2 U' R2 U2 R"),
+    ("Headlights", "R2 D' R U2 R' D R U2 R"),
+    ("Chameleon", "r U R' U' r' F R F'"),
+    ("Bowtie", "F' r U R' U' r' F R"),
+    ("Antisune", "R U2 R' U' R U' R'"),
+    ("Sune", "R U R' U R U2 R'"),
+    ("Stealth", "r U R' U' M U R U' R'"),
+    ("Awkward", "R U R' U' R U' R' F' U' F R U R'"),
+    ("Awkward", "F R' F R2 U' R' U' R U R' F2"),
+    ("P", "R' U' F U R U' R' F' R"),
+    ("P", "L U F' U' L' U L F L'"),
+    ("T", "R U R' U' R' F R F'"),
+    ("C", "R U R2 U' R' F R U R U' F'"),
+    ("Fish", "R U2 R2 F R F' R U2 R'"),
+    ("W", "L' U' L U' L' U L U L F' L' F"),
+    ("Fish", "F R' F' R U R U' R'"),
+    ("W", "R U R' U R U' R' U' R' F R F'"),
+    ("Lightning", "L F' L' U' L U F U' L'"),
+    ("Lightning", "R' F R U R' U' F' U R"),
+    ("Awkward", "R U R' U R U2 R' F R U R' U' F'"),
+    ("Awkward", "R' U' R U' R' U2 R F R U R' U' F'"),
+    ("P", "F' U' L' U L F"),
+    ("P", "F U R U' R' F'"),
+    ("T", "F R U R' U' F'"),
+    ("C", "R' U' R' F R F' U R"),
+    ("Small L", "R' U' R' F R F' R' F R F' U R"),
+    ("Small L", "F R U R' U' R U R' U' F'"),
+    ("Small L", "r U' r2 U r2 U r2 U' r"),
+    ("Small L", "r' U r2 U' r2 U' r2 U r'"),
+    ("Line", "F U R U' R' U R U' R' F'"),
+    ("Line", "R U R' U R U' B U' B' R'"),
+    ("Small L", "l' U2 L U L' U' L U L' U l"),
+    ("Small L", "r U2 R' U' R U R' U' R U' r'"),
+    ("Line", "R' F R U R U' R2 F' R2 U' R' U R U R'"),
+    ("Line", "r' U' r U' R' U R U' R' U R r' U r"),
+    ("Stealth", "R U R' U' M' U R U' r'"),
+];
+
+/// Every PLL algorithm, along with its usual name
+pub const PLL_ALGS: [(&str, &str); 21] = [
+    ("Aa", "x R' U R' D2 R U' R' D2 R2 x'"),
+    ("Ab", "x R2 D2 R U R' D2 R U' R x'"),
+    ("E", "x' R U' R' D R U R' D' R U R' D R U' R' D' x"),
+    ("F", "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R"),
+    ("Ga", "R2 U R' U R' U' R U' R2 U' D R' U R D'"),
+    ("Gb", "R' U' R U D' R2 U R' U R U' R U' R2 D"),
+    ("Gc", "R2 U' R U' R U R' U R2 U D' R U' R' D"),
+    ("Gd", "R U R' U' D R2 U' R U' R' U R' U R2 D'"),
+    ("H", "M2 U M2 U2 M2 U M2"),
+    ("Ja", "x R2 F R F' R U2 r' U r U2 x'"),
+    ("Jb", "R U R' F' R U R' U' R' F R2 U' R'"),
+    (
+        "Na",
+        "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'",
+    ),
+    ("Nb", "R' U R U' R' F' U' F R U R' F R' F' R U' R"),
+    ("Ra", "R U' R' U' R U R D R' U' R D' R' U2 R'"),
+    ("Rb", "R2 F R U R U' R' F' R U2 R' U2 R"),
+    ("T", "R U R' U' R' F R2 U' R' U' R U R' F'"),
+    ("Ua", "M2 U M U2 M' U M2"),
+    ("Ub", "M2 U' M U2 M' U' M2"),
+    ("V", "R' U R' U' y R' F' R2 U' R' U R' F R F"),
+    ("Y", "F R U' R' U' R U R' F' R U R' U' R' F R F'"),
+    ("Z", "M' U M2 U M2 U M' U2 M2"),
+];
+
+/// The ways to turn the U face before or after an algorithm; `None` is not turning it at all
+pub const AUFS: [Option<Amt>; 4] = [None, Some(Amt::One), Some(Amt::Two), Some(Amt::Rev)];
+
+/// The moves for an AUF; either a single U turn, or nothing
+pub fn auf_moves(auf: Option<Amt>) -> Vec<FullMove> {
+    auf.map(|amt| FullMove { dir: Dir::U, amt })
+        .into_iter()
+        .collect()
+}
+
+/// An OLL case, along with the U turn to do before its algorithm
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct OllCase {
+    /// The usual number of the case, from 1 to 57
+    pub number: usize,
+    pub pre_auf: Option<Amt>,
+}
+
+impl OllCase {
+    pub fn name(&self) -> &'static str {
+        OLL_ALGS[self.number - 1].0
+    }
+
+    pub fn alg(&self) -> &'static str {
+        OLL_ALGS[self.number - 1].1
+    }
+
+    /// The AUF and then the algorithm; this orients the last layer
+    pub fn moves(&self) -> Vec<FullMove> {
+        [auf_moves(self.pre_auf), parse_alg(self.alg())].concat()
+    }
+}
+
+impl Display for OllCase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OLL {} {}", self.number, self.name())
+    }
+}
+
+/// A PLL case, along with the U turns to do before and after its algorithm
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct PllCase {
+    /// The usual name of the case, e.g. "T" or "Ga"
+    pub name: &'static str,
+    pub pre_auf: Option<Amt>,
+    pub post_auf: Option<Amt>,
+}
+
+impl PllCase {
+    pub fn alg(&self) -> &'static str {
+        PLL_ALGS
+            .iter()
+            .find(|(name, _)| *name == self.name)
+            .map(|(_, alg)| *alg)
+            .unwrap()
+    }
+
+    /// The AUF, the algorithm, then the AUF after; this solves the cube
+    pub fn moves(&self) -> Vec<FullMove> {
+        [
+            auf_moves(self.pre_auf),
+            parse_alg(self.alg()),
+            auf_moves(self.post_auf),
+        ]
+        .concat()
+    }
+}
+
+impl Display for PllCase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-perm", self.name)
+    }
+}
+
+/// Figures out which OLL case the cube is in, by trying every algorithm from every angle; the
+/// first two layers should be solved, with the last layer on top. Returns None if the last layer
+/// is already oriented.
+pub fn recognize_oll(cube: &Cube) -> Option<OllCase> {
+    assert!(
+        to_cross_and_pairs(cube.clone(), &ALL_SLOTS).is_solved(),
+        "First two layers should be solved before OLL"
+    );
+
+    if to_oll(cube.clone()).is_solved() {
+        return None;
+    }
+
+    for pre_auf in AUFS {
+        for number in 1..=OLL_ALGS.len() {
+            let case = OllCase { number, pre_auf };
+
+            if to_oll(cube.clone().apply_many(&case.moves())).is_solved() {
+                return Some(case);
+            }
+        }
+    }
+
+    panic!("No OLL algorithm worked, which should be impossible")
+}
+
+/// Figures out which PLL case the cube is in, the same way as [recognize_oll]; the last layer
+/// should be oriented. Returns None if the last layer is already permuted, in which case
+/// [recognize_auf] says how to finish.
+pub fn recognize_pll(cube: &Cube) -> Option<PllCase> {
+    assert!(
+        to_oll(cube.clone()).is_solved(),
+        "Last layer should be oriented before PLL"
+    );
+
+    if recognize_auf(cube).is_some() {
+        return None;
+    }
+
+    for pre_auf in AUFS {
+        for (name, _) in PLL_ALGS {
+            for post_auf in AUFS {
+                let case = PllCase {
+                    name,
+                    pre_auf,
+                    post_auf,
+                };
+
+                if cube.clone().apply_many(&case.moves()).is_solved() {
+                    return Some(case);
+                }
+            }
+        }
+    }
+
+    panic!("No PLL algorithm worked, which should be impossible")
+}
+
+/// The U turn which solves the cube, if there is one; the outer None means no U turn does it,
+/// and `Some(None)` means it's solved already
+pub fn recognize_auf(cube: &Cube) -> Option<Option<Amt>> {
+    AUFS.into_iter()
+        .find(|&auf| cube.clone().apply_many(&auf_moves(auf)).is_solved())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cube::{AmbigFacelet, Facelet};
+    use crate::moves::{invert, parse_many};
+
+    use super::*;
+
+    fn solved() -> Cube {
+        Cube::make_solved(Facelet::Green, Facelet::Yellow)
+    }
+
+    /// The case an algorithm solves, as every way it can look up to turning the U face
+    fn case_of<T: Eq>(alg: &str, project: impl Fn(Cube) -> T) -> Vec<T> {
+        let start = solved().apply_many(&invert(&parse_alg(alg)));
+
+        ["", "U", "U2", "U'"]
+            .iter()
+            .map(|auf| project(start.clone().apply_many(&parse_many(auf))))
+            .collect()
+    }
+
+    #[test]
+    fn algs_keep_first_two_layers() {
+        let all_algs = OLL_ALGS
+            .iter()
+            .map(|(_, alg)| *alg)
+            .chain(PLL_ALGS.iter().map(|(_, alg)| *alg));
+
+        for alg in all_algs {
+            let cube = solved().apply_many(&parse_alg(alg));
+            assert!(
+                to_cross_and_pairs(cube.clone(), &ALL_SLOTS).is_solved(),
+                "{alg} should keep F2L"
+            );
+            assert!(!cube.is_solved(), "{alg} should do something");
+        }
+    }
+
+    #[test]
+    fn oll_cases_are_all_different() {
+        let cases: Vec<Vec<Cube<AmbigFacelet>>> = OLL_ALGS
+            .iter()
+            .map(|(_, alg)| case_of(alg, to_oll))
+            .collect();
+
+        for (i, case) in cases.iter().enumerate() {
+            assert!(
+                !case[0].is_solved(),
+                "OLL {} should not be oriented already",
+                i + 1
+            );
+
+            for (j, other) in cases.iter().enumerate().take(i) {
+                assert!(
+                    !case.contains(&other[0]),
+                    "OLL {} and OLL {} are the same case",
+                    j + 1,
+                    i + 1
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pll_cases_are_all_different() {
+        // PLL cases don't care how the U face is turned at the end either
+        let project = |cube: Cube| {
+            ["", "U", "U2", "U'"]
+                .iter()
+                .map(|auf| cube.clone().apply_many(&parse_many(auf)))
+                .collect::<Vec<Cube>>()
+        };
+
+        let cases: Vec<Vec<Vec<Cube>>> = PLL_ALGS
+            .iter()
+            .map(|(_, alg)| case_of(alg, project))
+            .collect();
+
+        for (i, case) in cases.iter().enumerate() {
+            let (name, alg) = PLL_ALGS[i];
+
+            assert!(
+                to_oll(case[0][0].clone()).is_solved(),
+                "{name} perm ({alg}) should not change orientation"
+            );
+
+            for (j, other) in cases.iter().enumerate().take(i) {
+                assert!(
+                    !case.iter().flatten().any(|c| c == &other[0][0]),
+                    "{} and {name} perms are the same case",
+                    PLL_ALGS[j].0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn recognizes_every_oll() {
+        for (i, (_, alg)) in OLL_ALGS.iter().enumerate() {
+            for auf in AUFS {
+                let cube = solved()
+                    .apply_many(&invert(&parse_alg(alg)))
+                    .apply_many(&auf_moves(auf));
+
+                let case = recognize_oll(&cube).unwrap();
+                assert_eq!(case.number, i + 1);
+                assert!(to_oll(cube.apply_many(&case.moves())).is_solved());
+            }
+        }
+    }
+
+    #[test]
+    fn recognizes_every_pll() {
+        for (name, alg) in PLL_ALGS {
+            for pre in AUFS {
+                for post in AUFS {
+                    let cube = solved()
+                        .apply_many(&auf_moves(post))
+                        .apply_many(&invert(&parse_alg(alg)))
+                        .apply_many(&auf_moves(pre));
+
+                    let case = recognize_pll(&cube).unwrap();
+                    assert_eq!(case.name, name);
+                    assert!(cube.apply_many(&case.moves()).is_solved());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn names_and_skips() {
+        let sune = solved().apply_many(&invert(&parse_alg("R U R' U R U2 R'")));
+        assert_eq!(recognize_oll(&sune).unwrap().to_string(), "OLL 27 Sune");
+
+        let t_perm = solved().apply_many(&parse_alg("R U R' U' R' F R2 U' R' U' R U R' F'"));
+        assert_eq!(recognize_oll(&t_perm), None);
+        assert_eq!(recognize_pll(&t_perm).unwrap().to_string(), "T-perm");
+
+        let off_by_one = solved().apply_many(&parse_many("U"));
+        assert_eq!(recognize_pll(&off_by_one), None);
+        assert_eq!(recognize_auf(&off_by_one), Some(Some(Amt::Rev)));
+        assert_eq!(recognize_auf(&solved()), Some(None));
+        assert_eq!(recognize_auf(&t_perm), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn needs_first_two_layers() {
+        recognize_oll(&solved().apply_many(&parse_many("R")));
+    }
+}
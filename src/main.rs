@@ -2,32 +2,51 @@ use std::time::{Duration, Instant};
 
 use clap::{Parser, Subcommand};
 use itertools::concat;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
+use crate::analysis::AlgEffect;
 use crate::corner_orientation_state::CornerOrientationState;
-use crate::cube::Facelet;
+use crate::cube::{ColorScheme, Facelet};
 use crate::edge_orientation_state::EdgeOrientationState;
 use crate::edge_slice_state::EdgeMidSliceState;
 use crate::kociemba::KociembaCaches;
 use crate::moves::{parse_many, to_nice_str, ApplyMove, FullMove};
-use crate::shadow::to_white_cross;
+use crate::scramble::{Difficulty, Subset};
+use crate::shadow::{to_cross_and_pairs, to_white_cross, Slot};
 use crate::timed::timed;
+use crate::transform::{Plane, Rotation};
 
+mod alg_database;
+mod analysis;
+mod beginner;
+mod cfop;
 mod corner_orientation_state;
 mod corner_position_state;
+mod cstimer;
 mod cube;
 mod dfs_util;
 mod edge_orientation_state;
 mod edge_position_state;
 mod edge_slice_state;
+mod generators;
 mod heuristic_caches;
 mod kociemba;
+mod last_layer;
 mod moves;
+mod playback;
+mod render;
+mod roux;
 mod scramble;
+mod session;
 mod shadow;
 mod solve;
+mod svg;
 mod thistlethwaite;
 mod timed;
 mod total_position_state;
+mod transform;
+mod zz;
 
 fn kociemba_stuff(input: &str, kociemba_cache: &kociemba::KociembaCaches) {
     let start = Instant::now();
@@ -68,7 +87,7 @@ fn kociemba_stuff(input: &str, kociemba_cache: &kociemba::KociembaCaches) {
         to_nice_str(&total_solution)
     );
 
-    let timings = vec![
+    let timings = [
         (h1_dur, "H0 to H1", h1_solution.len()),
         (h2_dur, "H1 to H2", h2_solution.len()),
     ];
@@ -131,7 +150,7 @@ fn thistle_stuff(input: &str, thistle_cache: &thistlethwaite::ThistlethwaiteCach
         total_solution.len(),
         to_nice_str(&total_solution)
     );
-    let timings = vec![
+    let timings = [
         (g1_dur, "G0 to G1", g1_solution.len()),
         (g2_dur, "G1 to G2", g2_solution.len()),
         (g3_dur, "G2 to G3", g3_solution.len()),
@@ -362,21 +381,968 @@ fn big_suite() {
     );
 }
 
-fn scramble_things() {
+fn parse_scheme(scheme: &str) -> ColorScheme {
+    ColorScheme::try_from(scheme)
+        .map_err(|e| panic!("Bad color scheme: {}", e))
+        .unwrap()
+}
+
+fn parse_color(color: &str) -> Facelet {
+    match color {
+        "Y" => Facelet::Yellow,
+        "W" => Facelet::White,
+        "G" => Facelet::Green,
+        "B" => Facelet::Blue,
+        "R" => Facelet::Red,
+        "O" => Facelet::Orange,
+        other => panic!("Bad color: {other} (expected one of Y W G B R O)"),
+    }
+}
+
+/// Solved cube in WCA orientation (white top, green front) if the scheme allows it; otherwise
+/// the scheme is held the way it was described
+fn wca_solved(scheme: &ColorScheme) -> cube::Cube {
+    // faces come in opposite pairs, so this is every pair of opposite colors
+    let opposites = [
+        [&scheme.u, &scheme.d],
+        [&scheme.f, &scheme.b],
+        [&scheme.l, &scheme.r],
+    ];
+
+    let wca_possible = !opposites
+        .iter()
+        .any(|pair| pair.contains(&&Facelet::White) && pair.contains(&&Facelet::Green));
+
+    if wca_possible {
+        cube::Cube::make_solved_with(scheme, Facelet::Green, Facelet::White)
+    } else {
+        cube::Cube::make_solved_with(scheme, scheme.f.clone(), scheme.u.clone())
+    }
+}
+
+fn parse_subset(subset: &str) -> Subset {
+    Subset::try_from(subset).unwrap_or_else(|e| panic!("Bad subset: {e}"))
+}
+
+/// Everything the scramblers need for solving and for checking difficulty
+struct ScrambleCaches {
+    kociemba: KociembaCaches,
+    generators: generators::GeneratorCaches,
+    cross: solve::CrossCache,
+}
+
+impl ScrambleCaches {
+    fn initialize() -> Self {
+        Self {
+            kociemba: KociembaCaches::initialize(),
+            generators: generators::GeneratorCaches::initialize(),
+            cross: solve::CrossCache::initialize(),
+        }
+    }
+}
+
+/// Solves a subset scramble, unless it's too easy; domino scrambles skip the first Kociemba
+/// phase, so the scramble stays in <L, R, U2, D2, F2, B2> like it should, and 2-gen and 3-gen
+/// ones don't use Kociemba at all, so they only turn their own faces
+fn solve_subset(
+    cube: &cube::Cube,
+    subset: Subset,
+    difficulty: &Difficulty,
+    caches: &ScrambleCaches,
+) -> Option<Vec<FullMove>> {
+    let phase1 = |cube: &cube::Cube| match subset {
+        Subset::Domino | Subset::TwoGen | Subset::ThreeGen => Vec::new(),
+        _ => kociemba::solve_to_h1(cube, &caches.kociemba.h0h1cache),
+    };
+    let phase2 = |cube: &cube::Cube| match subset {
+        Subset::TwoGen => generators::solve_two_gen(cube, &caches.generators),
+        Subset::ThreeGen => generators::solve_three_gen(cube, &caches.generators),
+        _ => kociemba::solve_to_h2(cube, &caches.kociemba.h1h2cache),
+    };
+
+    difficulty.solve(cube, &caches.cross, phase1, phase2)
+}
+
+/// Makes sure every scramble really does give the state it was made from
+fn check_scrambles(scheme: &ColorScheme, scrambles: &[scramble::Scramble]) {
+    for scramble in scrambles {
+        assert!(
+            scramble.is_valid(scheme),
+            "Scramble doesn't give its own state: {}",
+            to_nice_str(&scramble.moves)
+        );
+    }
+}
+
+/// Where the scrambles go, and what goes with them
+struct ScrambleOutput<'a> {
+    /// File for the scrambles; stdout if there isn't one
+    out: Option<&'a str>,
+    /// text or json
+    format: &'a str,
+    /// Folder for a picture of each scramble, if any
+    images: Option<&'a str>,
+}
+
+/// File name for the picture of scramble `i` (from zero) out of `count`; numbered from one, with
+/// enough zeros that they sort properly
+fn image_path(dir: &str, i: usize, count: usize) -> std::path::PathBuf {
+    let width = count.to_string().len();
+    std::path::Path::new(dir).join(format!("scramble-{:0width$}.svg", i + 1))
+}
+
+/// Solved cube held the way a scramble from the subset is meant to be done. Full scrambles use
+/// WCA orientation, but the subsets are built with the scheme's own U color on top (yellow, for
+/// Western), so the last layer is on U and whatever they keep solved is underneath it.
+fn scramble_hold(scheme: &ColorScheme, subset: Subset) -> cube::Cube {
+    match subset {
+        Subset::Full => wca_solved(scheme),
+        _ => cube::Cube::make_solved_with(scheme, scheme.f.clone(), scheme.u.clone()),
+    }
+}
+
+/// Draws the net of each scramble in the hold it's meant for (see [scramble_hold]), so a printed
+/// sheet can be checked against the real thing
+fn write_images(scheme: &ColorScheme, subset: Subset, scrambles: &[scramble::Scramble], dir: &str) {
+    std::fs::create_dir_all(dir).expect("Should be able to make the image folder");
+
+    for (i, scramble) in scrambles.iter().enumerate() {
+        let cube = scramble_hold(scheme, subset).apply_many(&scramble.moves);
+        std::fs::write(image_path(dir, i, scrambles.len()), svg::net_svg(&cube))
+            .expect("Should be able to write the image");
+    }
+}
+
+fn scramble_things(
+    scheme: &str,
+    subset: &str,
+    difficulty: Difficulty,
+    seed: Option<u64>,
+    count: usize,
+    output: ScrambleOutput,
+) {
+    let subset = parse_subset(subset);
+
+    if subset == Subset::Domino && difficulty.min_phase1 > 0 {
+        panic!("Domino scrambles are already in H1, so there's no first phase to be long");
+    }
+
+    if subset.generators().is_some() && difficulty.min_phase1 > 0 {
+        panic!("{subset} scrambles don't use Kociemba, so there's no first phase to be long");
+    }
+
+    if subset.keeps_cross() && difficulty.min_cross > 0 {
+        panic!("{subset} scrambles always leave the cross solved, so it can't take any moves");
+    }
+
+    if difficulty.max_solution > 0 && difficulty.max_solution < difficulty.min_solution {
+        panic!("The longest a scramble can be is shorter than the shortest it can be");
+    }
+
+    if output.format != "text" && output.format != "json" {
+        panic!("Bad format: {} (expected text or json)", output.format);
+    }
+
+    // JSON always goes through the batch writer, even for just one scramble
+    if count != 1 || output.out.is_some() || output.format == "json" {
+        return scramble_batch_things(scheme, subset, difficulty, seed, count, output);
+    }
+
+    let scheme = parse_scheme(scheme);
+
     println!("Warming up solver cache ...");
     let start = Instant::now();
-    let cache = KociembaCaches::initialize();
+    let caches = ScrambleCaches::initialize();
     println!("Cache ready (took {:?})", start.elapsed());
 
-    let scrambled = scramble::scramble_any();
+    let (elapsed, scrambles) = timed(|| {
+        scramble::scramble_batch(&scheme, subset, 1, seed, 1, &|cube: &cube::Cube| {
+            solve_subset(cube, subset, &difficulty, &caches)
+        })
+    });
+    check_scrambles(&scheme, &scrambles);
 
-    let start = Instant::now();
-    let solution = kociemba::full_solve(&scrambled, &cache);
-    let elapsed = start.elapsed();
+    println!(
+        "Found scramble (in {elapsed:?}): {}",
+        to_nice_str(&scrambles[0].moves)
+    );
 
-    let rev = moves::invert(&solution);
+    if subset != Subset::Full {
+        println!(
+            "Hold the cube with {:?} on top and {:?} in front",
+            scheme.u, scheme.f
+        );
+    }
 
-    println!("Found scramble (in {elapsed:?}): {}", to_nice_str(&rev));
+    if let Some(dir) = output.images {
+        write_images(&scheme, subset, &scrambles, dir);
+        println!("Drew it in {}", image_path(dir, 0, 1).display());
+    }
+}
+
+fn scramble_batch_things(
+    scheme: &str,
+    subset: Subset,
+    difficulty: Difficulty,
+    seed: Option<u64>,
+    count: usize,
+    output: ScrambleOutput,
+) {
+    let ScrambleOutput {
+        out,
+        format,
+        images,
+    } = output;
+
+    let scheme = parse_scheme(scheme);
+
+    // if the scrambles are going to stdout, keep everything else out of the way
+    let status = |msg: String| match out {
+        Some(_) => println!("{msg}"),
+        None => eprintln!("{msg}"),
+    };
+
+    status("Warming up solver cache ...".to_string());
+    let (cache_dur, caches) = timed(ScrambleCaches::initialize);
+    status(format!("Cache ready (took {cache_dur:?})"));
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    let (dur, scrambles) = timed(|| {
+        scramble::scramble_batch(
+            &scheme,
+            subset,
+            count,
+            seed,
+            threads,
+            &|cube: &cube::Cube| solve_subset(cube, subset, &difficulty, &caches),
+        )
+    });
+    check_scrambles(&scheme, &scrambles);
+    status(format!(
+        "Found {count} scrambles with {threads} threads (in {dur:?})"
+    ));
+
+    if subset != Subset::Full {
+        status(format!(
+            "Hold the cube with {:?} on top and {:?} in front",
+            scheme.u, scheme.f
+        ));
+    }
+
+    if let Some(dir) = images {
+        write_images(&scheme, subset, &scrambles, dir);
+        status(format!("Drew them in {dir}"));
+    }
+
+    let text = if format == "json" {
+        let entries: Vec<serde_json::Value> = scrambles
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let mut entry = serde_json::json!({
+                    "scramble": to_nice_str(&s.moves),
+                    "moves": s.moves.len(),
+                });
+                if let Some(dir) = images {
+                    let path = image_path(dir, i, count).display().to_string();
+                    entry["image"] = serde_json::Value::String(path);
+                }
+                entry
+            })
+            .collect();
+
+        let json = serde_json::to_string_pretty(&entries).expect("Should be able to write JSON");
+        format!("{json}\n")
+    } else {
+        scrambles
+            .iter()
+            .map(|s| format!("{}\n", to_nice_str(&s.moves)))
+            .collect()
+    };
+
+    match out {
+        Some(path) => {
+            std::fs::write(path, text).expect("Should be able to write the scrambles");
+            println!("Wrote them to {path}");
+        }
+        None => print!("{text}"),
+    }
+}
+
+/// Faces given as letters, like "RUF"
+fn parse_faces(faces: &str) -> Vec<moves::Dir> {
+    faces
+        .chars()
+        .map(|c| {
+            let letter = c.to_string();
+            FullMove::try_from(letter.as_str())
+                .map(|fm| fm.dir)
+                .unwrap_or_else(|_| panic!("Bad face: {c} (expected some of U D F B L R)"))
+        })
+        .collect()
+}
+
+fn random_moves_things(
+    length: usize,
+    faces: &str,
+    count: usize,
+    seed: Option<u64>,
+    solve: bool,
+    scheme: &str,
+) {
+    let scheme = parse_scheme(scheme);
+    let dirs = parse_faces(faces);
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => {
+            StdRng::from_rng(rand::thread_rng()).expect("Should be able to seed from thread_rng")
+        }
+    };
+
+    let scrambles: Vec<Vec<FullMove>> = (0..count)
+        .map(|_| scramble::random_moves(&dirs, length, &mut rng))
+        .collect();
+
+    if !solve {
+        for moves in &scrambles {
+            println!("{}", to_nice_str(moves));
+        }
+        return;
+    }
+
+    // the same as a benchmark, but with however deep a position is wanted
+    let (cache_dur, cache) = timed(KociembaCaches::initialize);
+    println!("Kociemba cache ready (took {cache_dur:?})");
+
+    let solved = cube::Cube::make_solved_with(&scheme, scheme.f.clone(), scheme.u.clone());
+    let mut longest = 0;
+    let mut slowest = Duration::ZERO;
+
+    for moves in &scrambles {
+        let cube = solved.clone().apply_many(moves);
+        let (dur, solution) = timed(|| kociemba::full_solve(&cube, &cache));
+
+        assert!(
+            cube.apply_many(&solution).is_solved(),
+            "Kociemba didn't solve {}",
+            to_nice_str(moves)
+        );
+
+        println!("{}", to_nice_str(moves));
+        println!(
+            "    solved in {} moves (in {dur:?}): {}",
+            solution.len(),
+            to_nice_str(&solution)
+        );
+
+        longest = longest.max(solution.len());
+        slowest = slowest.max(dur);
+    }
+
+    println!("Solved all {count} (longest solution {longest} moves, slowest took {slowest:?})");
+}
+
+/// Seconds since the Unix epoch, for dating solves
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// One line from the terminal, trimmed; None once there's nothing left to read
+fn read_line() -> Option<String> {
+    let mut line = String::new();
+    match std::io::stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
+
+/// Times solves in the terminal, one scramble at a time, until it's told to stop; each solve is
+/// saved as soon as it's done
+fn run_timer(session: &mut session::Session, file: &str, scheme: &str) {
+    let scheme = parse_scheme(scheme);
+
+    println!("Warming up solver cache ...");
+    let (cache_dur, caches) = timed(ScrambleCaches::initialize);
+    println!("Cache ready (took {cache_dur:?})");
+
+    loop {
+        let scrambles =
+            scramble::scramble_batch(&scheme, Subset::Full, 1, None, 1, &|cube: &cube::Cube| {
+                solve_subset(cube, Subset::Full, &Difficulty::default(), &caches)
+            });
+        check_scrambles(&scheme, &scrambles);
+        let scramble = to_nice_str(&scrambles[0].moves);
+
+        println!();
+        println!("Scramble: {scramble}");
+        println!("Press Enter to start the timer (or q, then Enter, to stop)");
+
+        match read_line() {
+            Some(line) if line.eq_ignore_ascii_case("q") => break,
+            None => break,
+            Some(_) => {}
+        }
+
+        let start = Instant::now();
+        println!("Solving ... press Enter to stop");
+        if read_line().is_none() {
+            break;
+        }
+        let time = start.elapsed();
+
+        println!("Time: {}", session::format_time(time));
+        println!("Enter to keep it, 2 for +2, d for DNF");
+
+        let penalty = loop {
+            let line = read_line().unwrap_or_default();
+            match session::Penalty::try_from(line.as_str()) {
+                Ok(penalty) => break penalty,
+                Err(other) => println!("Bad penalty: {other} (expected nothing, 2 or d)"),
+            }
+        };
+
+        session.push(session::Solve {
+            time,
+            penalty,
+            scramble,
+            date: now(),
+        });
+        session
+            .save(file)
+            .unwrap_or_else(|e| panic!("Couldn't save the session: {e}"));
+
+        println!("{}", session.stats());
+    }
+}
+
+fn session_things(
+    file: &str,
+    time: Option<&str>,
+    penalty: &str,
+    scramble: Option<&str>,
+    timer: bool,
+    export: Option<&str>,
+    scheme: &str,
+) {
+    let mut session =
+        session::Session::load(file).unwrap_or_else(|e| panic!("Couldn't load the session: {e}"));
+
+    if let Some(time) = time {
+        let time = session::parse_time(time).unwrap_or_else(|e| panic!("{e}"));
+        let penalty = session::Penalty::try_from(penalty)
+            .unwrap_or_else(|p| panic!("Bad penalty: {p} (expected ok, +2 or dnf)"));
+
+        session.push(session::Solve {
+            time,
+            penalty,
+            // parsing it catches typos, and tidies up the spacing
+            scramble: to_nice_str(&parse_many(scramble.unwrap_or_default())),
+            date: now(),
+        });
+        session
+            .save(file)
+            .unwrap_or_else(|e| panic!("Couldn't save the session: {e}"));
+        println!(
+            "Added {} to {file}",
+            session.solves().last().unwrap().outcome()
+        );
+    }
+
+    if timer {
+        run_timer(&mut session, file, scheme);
+    }
+
+    if let Some(path) = export {
+        std::fs::write(path, session.to_csv()).expect("Should be able to write the CSV");
+        println!("Exported {} solves to {path}", session.solves().len());
+    }
+
+    println!("{}", session.stats());
+}
+
+fn import_things(file: &str, csv: Option<&str>) {
+    let text = std::fs::read_to_string(file).expect("Should be able to read the export");
+    let sessions =
+        cstimer::parse_export(&text).unwrap_or_else(|e| panic!("Couldn't read {file}: {e}"));
+
+    println!("Warming up solver caches ...");
+    let (cache_dur, caches) = timed(ScrambleCaches::initialize);
+    println!("Caches ready (took {cache_dur:?})");
+
+    let mut rows = vec![
+        "Session,Number,Time,Penalty,Result,Scramble,Cross,White cross,Kociemba,Problem"
+            .to_string(),
+    ];
+
+    for imported in &sessions {
+        let solves = imported.session.solves();
+
+        println!();
+        println!("== {} ({} solves) ==", imported.name, solves.len());
+        println!("{}", imported.session.stats());
+
+        // the times still count, but the scrambles are for some other puzzle, so there's nothing
+        // to check
+        let skipped =
+            (!imported.is_3x3()).then(|| format!("Not a 3x3 session ({})", imported.scramble_type));
+        if let Some(reason) = &skipped {
+            println!("{reason}, so its scrambles are skipped");
+        }
+
+        let mut totals = (0, 0, 0);
+        let mut valid = 0;
+
+        for (i, solve) in solves.iter().enumerate() {
+            let stats = match &skipped {
+                Some(reason) => Err(reason.clone()),
+                None => cstimer::parse_scramble(&solve.scramble).map(|cube| {
+                    cstimer::scramble_stats(&cube, &caches.cross, |cube| {
+                        kociemba::full_solve(cube, &caches.kociemba)
+                    })
+                }),
+            };
+
+            let (columns, problem) = match &stats {
+                Ok(stats) => {
+                    println!(
+                        "    {:>4}. {:>8}  cross {} (white {}), Kociemba {}: {}",
+                        i + 1,
+                        solve.outcome().to_string(),
+                        stats.cross,
+                        stats.white_cross,
+                        stats.solution,
+                        solve.scramble
+                    );
+
+                    valid += 1;
+                    totals.0 += stats.cross;
+                    totals.1 += stats.white_cross;
+                    totals.2 += stats.solution;
+
+                    let columns =
+                        format!("{},{},{}", stats.cross, stats.white_cross, stats.solution);
+                    (columns, String::new())
+                }
+                Err(e) => {
+                    if skipped.is_none() {
+                        println!("    {:>4}. {:>8}  {e}", i + 1, solve.outcome().to_string());
+                    }
+                    (",,".to_string(), e.clone())
+                }
+            };
+
+            rows.push(format!(
+                "\"{}\",{},{},{},{},\"{}\",{columns},\"{}\"",
+                imported.name.replace('"', "\"\""),
+                i + 1,
+                session::format_time(solve.time),
+                solve.penalty,
+                solve.outcome(),
+                solve.scramble.replace('"', "\"\""),
+                problem.replace('"', "\"\"")
+            ));
+        }
+
+        if valid > 0 {
+            let mean = |total: usize| total as f64 / valid as f64;
+            println!(
+                "Mean over {valid} good scrambles: cross {:.2} (white {:.2}), Kociemba {:.2}",
+                mean(totals.0),
+                mean(totals.1),
+                mean(totals.2)
+            );
+        }
+    }
+
+    if let Some(path) = csv {
+        std::fs::write(path, rows.join("\n") + "\n").expect("Should be able to write the CSV");
+        println!();
+        println!("Wrote every solve to {path}");
+    }
+}
+
+fn transform_things(alg: &str, mirror: Option<&str>, rotate: Option<&str>) {
+    let original = parse_many(alg);
+
+    let mut transformed = original.clone();
+    // what the transformed algorithm should do, according to the cube model
+    let mut expected = cube::Cube::make_solved(Facelet::Green, Facelet::Yellow);
+
+    if let Some(plane) = mirror {
+        let plane = Plane::try_from(plane)
+            .map_err(|e| panic!("Bad plane: {}", e))
+            .unwrap();
+        transformed = transform::mirror(&transformed, plane);
+        expected = expected.mirrored(plane);
+    }
+
+    if let Some(rotation) = rotate {
+        let rotation = Rotation::try_from(rotation)
+            .map_err(|e| panic!("Bad rotation: {}", e))
+            .unwrap();
+        transformed = transform::rotate(&transformed, rotation);
+        expected = expected.rotated(rotation);
+    }
+
+    // undo the transformations of the cube, in reverse order
+    expected = expected.apply_many(&original);
+    if let Some(rotation) = rotate {
+        expected = expected.rotated(Rotation::try_from(rotation).unwrap().inverse());
+    }
+    if let Some(plane) = mirror {
+        expected = expected.mirrored(Plane::try_from(plane).unwrap());
+    }
+
+    assert_eq!(
+        cube::Cube::make_solved(Facelet::Green, Facelet::Yellow).apply_many(&transformed),
+        expected,
+        "Transformed algorithm should agree with the cube model"
+    );
+
+    println!("{}", to_nice_str(&transformed));
+}
+
+fn analyze_things(alg: &str) {
+    let effect = AlgEffect::from_moves(&parse_many(alg));
+
+    println!("{effect}");
+}
+
+fn show_things(scramble: &str, no_color: bool, scheme: &str) {
+    let cube = wca_solved(&parse_scheme(scheme)).apply_many(&parse_many(scramble));
+
+    // respect https://no-color.org as well as the flag
+    let color = !no_color && std::env::var_os("NO_COLOR").is_none();
+
+    println!("{}", render::render_net(&cube, color));
+}
+
+fn playback_things(scramble: &str, method: &str, no_color: bool, scheme: &str) {
+    let cube = wca_solved(&parse_scheme(scheme)).apply_many(&parse_many(scramble));
+
+    let phases = match method {
+        "kociemba" => {
+            let (cache_dur, cache) = timed(KociembaCaches::initialize);
+            println!("Kociemba cache ready (took {cache_dur:?})");
+            kociemba::solve_in_phases(&cube, &cache)
+        }
+        "thistlethwaite" => {
+            let (cache_dur, cache) = timed(thistlethwaite::ThistlethwaiteCaches::initialize);
+            println!("Thistlethwaite cache ready (took {cache_dur:?})");
+            thistlethwaite::solve_in_phases(&cube, &cache)
+        }
+        other => panic!("Bad method: {other} (expected kociemba or thistlethwaite)"),
+    };
+
+    // same as the show command
+    let color = !no_color && std::env::var_os("NO_COLOR").is_none();
+
+    let mut phase_start = 0;
+
+    for frame in playback::playback(&cube, &phases) {
+        match frame.last_move {
+            None => println!("Start:"),
+            Some(fm) => println!("{}. {fm}", frame.move_number),
+        }
+        println!("{}", render::render_net(&frame.cube, color));
+
+        for i in frame.finished {
+            let phase = &phases[i];
+            println!(
+                "== Reached {} ({} moves for this phase, {} so far) ==",
+                phase.reached,
+                frame.move_number - phase_start,
+                frame.move_number
+            );
+            println!("   {}", phase.invariant);
+            println!();
+            phase_start = frame.move_number;
+        }
+    }
+
+    let moves: Vec<FullMove> = phases.iter().flat_map(|p| p.moves.clone()).collect();
+    println!("Solution ({} moves): {}", moves.len(), to_nice_str(&moves));
+}
+
+fn image_things(scramble: &str, view: &str, out: Option<&str>, scheme: &str) {
+    let cube = wca_solved(&parse_scheme(scheme)).apply_many(&parse_many(scramble));
+
+    let image = match view {
+        "net" => svg::net_svg(&cube),
+        "iso" => svg::isometric_svg(&cube),
+        "last-layer" => svg::last_layer_svg(&cube),
+        other => panic!("Bad view: {other} (expected net, iso or last-layer)"),
+    };
+
+    match out {
+        Some(path) => std::fs::write(path, image).expect("Should be able to write the image"),
+        None => print!("{image}"),
+    }
+}
+
+fn cross_things(scramble: &str, scheme: &str) {
+    let cube = wca_solved(&parse_scheme(scheme)).apply_many(&parse_many(scramble));
+
+    let (cache_dur, cache) = timed(solve::CrossCache::initialize);
+    println!("Cross cache ready (took {cache_dur:?})");
+
+    let (dur, all) = timed(|| solve::solve_all_crosses(&cube, &cache));
+
+    for sol in &all {
+        let rotation = sol.rotation.map(|r| format!("{r} ")).unwrap_or_default();
+        println!(
+            "    {:?}: {} moves: {}{}",
+            sol.color,
+            sol.moves.len(),
+            rotation,
+            to_nice_str(&sol.moves)
+        );
+    }
+
+    let best = solve::best_cross(&all);
+    println!(
+        "Best cross is {:?} in {} moves (found all six in {dur:?})",
+        best.color,
+        best.moves.len()
+    );
+}
+
+fn xcross_things(scramble: &str, color: &str, slots: &[String], scheme: &str) {
+    let cube = wca_solved(&parse_scheme(scheme)).apply_many(&parse_many(scramble));
+
+    let color = parse_color(color);
+
+    let slots: Vec<Slot> = slots
+        .iter()
+        .map(|s| {
+            Slot::try_from(s.as_str())
+                .map_err(|e| panic!("Bad slot: {}", e))
+                .unwrap()
+        })
+        .collect();
+
+    let (cache_dur, cache) = timed(solve::XCrossCache::initialize);
+    println!("XCross cache ready (took {cache_dur:?})");
+
+    let (dur, sol) = timed(|| solve::solve_xcross(&cube, &color, &slots, &cache));
+
+    let mut held = cube.clone();
+    if let Some(rotation) = sol.rotation {
+        held = held.rotated(rotation);
+    }
+    assert!(
+        to_cross_and_pairs(held.apply_many(&sol.moves), &slots).is_solved(),
+        "Cross and pairs should be solved, that's the point"
+    );
+
+    let rotation = sol.rotation.map(|r| format!("{r} ")).unwrap_or_default();
+    println!(
+        "{:?} cross with {:?} in {} moves (took {dur:?}): {}{}",
+        sol.color,
+        sol.slots,
+        sol.moves.len(),
+        rotation,
+        to_nice_str(&sol.moves)
+    );
+}
+
+fn cfop_things(scramble: &str, color: &str, scheme: &str) {
+    let cube = wca_solved(&parse_scheme(scheme)).apply_many(&parse_many(scramble));
+    let color = parse_color(color);
+
+    let (cache_dur, cache) = timed(solve::XCrossCache::initialize);
+    println!("XCross cache ready (took {cache_dur:?})");
+
+    let (dur, sol) = timed(|| cfop::solve_cfop(&cube, &color, &cache));
+
+    let mut held = cube.clone();
+    if let Some(rotation) = sol.rotation {
+        held = held.rotated(rotation);
+    }
+    assert!(held.apply_many(&sol.moves()).is_solved());
+
+    println!("{sol}");
+    println!("CFOP took {dur:?}");
+
+    let (cache_dur, kociemba_cache) = timed(KociembaCaches::initialize);
+    println!("Kociemba cache ready (took {cache_dur:?})");
+
+    let kociemba = kociemba::full_solve(&cube, &kociemba_cache);
+    println!(
+        "For comparison, Kociemba found {} moves: {}",
+        kociemba.len(),
+        to_nice_str(&kociemba)
+    );
+}
+
+fn roux_things(scramble: &str, color: &str, scheme: &str) {
+    let cube = wca_solved(&parse_scheme(scheme)).apply_many(&parse_many(scramble));
+    let color = parse_color(color);
+
+    let (cache_dur, cache) = timed(roux::RouxCache::initialize);
+    println!("Roux cache ready (took {cache_dur:?})");
+
+    let (dur, sol) = timed(|| roux::solve_roux(&cube, &color, &cache));
+
+    let mut held = cube.clone();
+    if let Some(rotation) = sol.rotation {
+        held = held.rotated(rotation);
+    }
+    assert!(held
+        .apply_many(&transform::parse_alg(&sol.alg()))
+        .is_solved());
+
+    println!("{sol}");
+    println!("Roux took {dur:?}");
+}
+
+fn beginner_things(scramble: &str, color: &str, scheme: &str) {
+    let cube = wca_solved(&parse_scheme(scheme)).apply_many(&parse_many(scramble));
+    let color = parse_color(color);
+
+    let (cache_dur, cache) = timed(beginner::BeginnerCache::initialize);
+    println!("Beginner cache ready (took {cache_dur:?})");
+
+    let (dur, sol) = timed(|| beginner::solve_beginner(&cube, &color, &cache));
+
+    let mut held = cube.clone();
+    if let Some(rotation) = sol.rotation {
+        held = held.rotated(rotation);
+    }
+    assert!(held.apply_many(&sol.moves()).is_solved());
+
+    println!("{sol}");
+    println!("Beginner method took {dur:?}");
+}
+
+fn zz_things(scramble: &str, color: &str, cross: bool, scheme: &str) {
+    let cube = wca_solved(&parse_scheme(scheme)).apply_many(&parse_many(scramble));
+    let color = parse_color(color);
+
+    // a ZZ solver cares about F/B, but the other two axes are interesting too
+    for (name, axis) in [
+        ("F/B", transform::Axis::Z),
+        ("U/D", transform::Axis::Y),
+        ("L/R", transform::Axis::X),
+    ] {
+        let state = edge_orientation_state::AxisEdgeOrientationState::from_cube(&cube, axis);
+        println!("Bad edges for {name}: {}", state.bad_count());
+    }
+
+    let (cache_dur, cache) = timed(zz::ZzCache::initialize);
+    println!("ZZ cache ready (took {cache_dur:?})");
+
+    let target = if cross {
+        zz::EoTarget::Cross
+    } else {
+        zz::EoTarget::Line
+    };
+
+    let (dur, sol) = timed(|| zz::solve_eo(&cube, &color, target, &cache));
+
+    println!("{sol}");
+    println!("{target} took {dur:?}");
+}
+
+fn mask_things(scramble: &str, spec: &str, max_moves: usize, scheme: &str) {
+    let cube = wca_solved(&parse_scheme(scheme)).apply_many(&parse_many(scramble));
+    let spec = shadow::MaskSpec::try_from(spec)
+        .map_err(|e| panic!("Couldn't parse mask: {e}"))
+        .unwrap();
+
+    println!("Mask: {spec} ({} pieces)", spec.pieces().len());
+
+    let (cache_dur, cache) = timed(|| solve::MaskCache::initialize(&spec));
+    println!("Mask cache ready (took {cache_dur:?})");
+
+    let (dur, moves) = timed(|| solve::solve_mask(&cube, &cache, max_moves));
+    assert!(spec.apply(cube.apply_many(&moves)).is_solved());
+
+    println!("Solution ({} moves): {}", moves.len(), to_nice_str(&moves));
+    println!("Solving took {dur:?}");
+}
+
+fn recognize_things(scramble: &str, scheme: &str) {
+    let scheme = parse_scheme(scheme);
+    // last layer cases are looked at with the last layer on top, which is how the scheme is held
+    let cube = cube::Cube::make_solved_with(&scheme, scheme.f.clone(), scheme.u.clone())
+        .apply_many(&transform::parse_alg(scramble));
+
+    if !to_cross_and_pairs(cube.clone(), &shadow::ALL_SLOTS).is_solved() {
+        println!("First two layers aren't solved, so this isn't a last layer case");
+        return;
+    }
+
+    if let Some(case) = last_layer::recognize_oll(&cube) {
+        println!("{case}: {}", to_nice_str(&case.moves()));
+    } else if let Some(case) = last_layer::recognize_pll(&cube) {
+        println!("{case}: {}", to_nice_str(&case.moves()));
+    } else {
+        match last_layer::recognize_auf(&cube).unwrap() {
+            Some(amt) => println!(
+                "Solved, after an AUF: {}",
+                to_nice_str(&last_layer::auf_moves(Some(amt)))
+            ),
+            None => println!("Already solved"),
+        }
+    }
+}
+
+fn algs_things(
+    scramble: Option<&str>,
+    case: Option<&str>,
+    set: &str,
+    files: &[String],
+    scheme: &str,
+) {
+    let set = alg_database::AlgSet::try_from(set)
+        .map_err(|e| panic!("Unknown algorithm set: {e}"))
+        .unwrap();
+
+    let mut db = alg_database::AlgDatabase::builtin();
+    for file in files {
+        db.extend(
+            alg_database::AlgDatabase::from_file(file)
+                .map_err(|e| panic!("Bad algorithm file: {e}"))
+                .unwrap(),
+        );
+    }
+    db.check_cases()
+        .map_err(|e| panic!("Algorithms disagree: {e}"))
+        .unwrap();
+
+    if let Some(case) = case {
+        for entry in db.lookup(set, case) {
+            println!("{entry}");
+        }
+        return;
+    }
+
+    let Some(scramble) = scramble else {
+        for entry in db.entries().iter().filter(|entry| entry.set == set) {
+            println!("{entry}");
+        }
+        return;
+    };
+
+    let scheme = parse_scheme(scheme);
+    let cube = cube::Cube::make_solved_with(&scheme, scheme.f.clone(), scheme.u.clone())
+        .apply_many(&transform::parse_alg(scramble));
+
+    let solutions = db.solutions(set, &cube);
+
+    if solutions.is_empty() {
+        println!("No {set} algorithms for this case");
+    }
+
+    for (entry, moves) in solutions {
+        println!("{entry}");
+        println!("    with AUFs, as face turns: {}", to_nice_str(&moves));
+    }
 }
 
 #[derive(Parser)]
@@ -388,7 +1354,285 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     Benchmark,
-    Scramble,
+    Scramble {
+        /// Color scheme of the cube: western, japanese, or the colors of the U D F B L R faces
+        /// as six letters, e.g. YWGBRO
+        #[arg(long, default_value = "western")]
+        scheme: String,
+        /// What to scramble: full, ll, f2l, 2gen, 3gen, edges, corners or domino. Full scrambles
+        /// are done the WCA way (white top, green front); the rest are done with the scheme's U
+        /// color on top (yellow, for western), so the last layer is on top.
+        #[arg(long, default_value = "full")]
+        subset: String,
+        /// Fewest moves the easiest cross (on any color) can take; easier scrambles are thrown out
+        #[arg(long, default_value_t = 0)]
+        min_cross: usize,
+        /// Fewest moves the first Kociemba phase can take
+        #[arg(long, default_value_t = 0)]
+        min_phase1: usize,
+        /// Fewest moves in the solution the scrambler finds (and so in the scramble). For most
+        /// subsets that's the Kociemba solution, which isn't always optimal, so the cube might
+        /// really be closer to solved than this.
+        #[arg(long, default_value_t = 0)]
+        min_solution: usize,
+        /// Most moves in that same solution; zero for no limit
+        #[arg(long, default_value_t = 0)]
+        max_solution: usize,
+        /// Seed for the random state; the same seed always gives the same scrambles. With more
+        /// than one scramble, each one gets its own seed drawn from this one, so nearby seeds
+        /// don't share scrambles.
+        #[arg(long)]
+        seed: Option<u64>,
+        /// How many scrambles to make; more than one are made in parallel, one per line
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        /// File to write the scrambles to, instead of printing them
+        #[arg(long)]
+        out: Option<String>,
+        /// How to write the scrambles: text (one per line) or json
+        #[arg(long, default_value = "text")]
+        format: String,
+        /// Folder to draw each scramble in, as an SVG net held the way the scramble is meant to
+        /// be done (see --subset)
+        #[arg(long)]
+        images: Option<String>,
+    },
+    /// Old-style scrambles of random moves, never turning a face twice in a row
+    RandomMoves {
+        /// How many moves in each scramble
+        #[arg(long, default_value_t = 25)]
+        length: usize,
+        /// Which faces can turn, as letters, e.g. RU for 2-gen
+        #[arg(long, default_value = "UDFBLR")]
+        faces: String,
+        /// How many scrambles to make
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        /// Seed for the moves; the same seed always gives the same scrambles
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Also solve each one with Kociemba, checking the solution works; handy for putting
+        /// the solver through shallow or deep positions
+        #[arg(long)]
+        solve: bool,
+        /// Color scheme of the cube: western, japanese, or the colors of the U D F B L R faces
+        /// as six letters, e.g. YWGBRO
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Keep track of practice solves: add a time, or time solves right here, and see the
+    /// averages
+    Session {
+        /// File the session is kept in; made if it isn't there yet
+        #[arg(long, default_value = "session.txt")]
+        file: String,
+        /// Add a solve with this time, e.g. 12.34 or 1:02.34
+        #[arg(long)]
+        time: Option<String>,
+        /// Penalty for the added solve: ok, +2 or dnf
+        #[arg(long, default_value = "ok")]
+        penalty: String,
+        /// Scramble for the added solve
+        #[arg(long)]
+        scramble: Option<String>,
+        /// Time solves in the terminal with the Enter key, with a new scramble for each one
+        #[arg(long)]
+        timer: bool,
+        /// Also write every solve to this CSV file
+        #[arg(long)]
+        export: Option<String>,
+        /// Color scheme of the cube, for the timer's scrambles: western, japanese, or the colors
+        /// of the U D F B L R faces as six letters, e.g. YWGBRO
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Read a csTimer export and work out how hard each 3x3 scramble was; sessions for other
+    /// puzzles only get their times
+    Import {
+        /// The exported JSON file
+        file: String,
+        /// Also write every solve, with its scramble's numbers, to this CSV file
+        #[arg(long)]
+        csv: Option<String>,
+    },
+    /// Mirror and/or rotate an algorithm; if both are given, the mirror happens first
+    Transform {
+        /// The algorithm to transform, e.g. "R U R' U'"
+        alg: String,
+        /// Plane to mirror across: M, E or S
+        #[arg(long)]
+        mirror: Option<String>,
+        /// Whole-cube rotation to conjugate by, e.g. y, x' or z2
+        #[arg(long)]
+        rotate: Option<String>,
+    },
+    /// Describe what an algorithm does: its corner and edge cycles, and its order
+    Analyze {
+        /// The algorithm to analyze, e.g. "R U R' U'"
+        alg: String,
+    },
+    /// Apply a scramble to a solved cube (white top, green front, if the scheme allows it) and
+    /// draw the result
+    Show {
+        /// The scramble to apply, e.g. "R U R' U'"
+        scramble: String,
+        /// Draw facelets as letters instead of ANSI colors
+        #[arg(long)]
+        no_color: bool,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Apply a scramble to a solved cube (white top, green front, if the scheme allows it) and
+    /// draw it as an SVG image
+    Image {
+        /// The scramble to apply, e.g. "R U R' U'"
+        scramble: String,
+        /// What to draw: net, iso (the U, F and R faces in 3D) or last-layer
+        #[arg(long, default_value = "net")]
+        view: String,
+        /// File to write the image to; if not given, it's printed instead
+        #[arg(long)]
+        out: Option<String>,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Find the optimal cross of every color for a scramble (white top, green front, if the
+    /// scheme allows it), along with the rotation to put it on the bottom
+    Cross {
+        /// The scramble to apply, e.g. "R U R' U'"
+        scramble: String,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Find the optimal cross plus some F2L pairs for a scramble (white top, green front, if the
+    /// scheme allows it); the slots are named for the cube once the cross is on the bottom
+    Xcross {
+        /// The scramble to apply, e.g. "R U R' U'"
+        scramble: String,
+        /// Cross color, as a letter: Y W G B R or O
+        #[arg(long, default_value = "W")]
+        color: String,
+        /// Slots to solve along with the cross: FL, FR, BL or BR; can be given more than once
+        #[arg(long = "slot")]
+        slots: Vec<String>,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Solve a scramble (white top, green front, if the scheme allows it) the way a person
+    /// would with CFOP, stage by stage, and compare it to the Kociemba solver
+    Cfop {
+        /// The scramble to apply, e.g. "R U R' U'"
+        scramble: String,
+        /// Cross color, as a letter: Y W G B R or O
+        #[arg(long, default_value = "W")]
+        color: String,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Solve a scramble (white top, green front, if the scheme allows it) with Roux: first
+    /// block, second block, CMLL and LSE
+    Roux {
+        /// The scramble to apply, e.g. "R U R' U'"
+        scramble: String,
+        /// Color for the bottom of the blocks, as a letter: Y W G B R or O
+        #[arg(long, default_value = "W")]
+        color: String,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Solve a scramble (white top, green front, if the scheme allows it) with Kociemba or
+    /// Thistlethwaite, and play the solution back a move at a time, showing the cube after each
+    /// move and what each phase achieved
+    Playback {
+        /// The scramble to apply, e.g. "R U R' U'"
+        scramble: String,
+        /// Which solver to use: kociemba or thistlethwaite
+        #[arg(long, default_value = "kociemba")]
+        method: String,
+        /// Use letters instead of colors
+        #[arg(long)]
+        no_color: bool,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Solve a scramble (white top, green front, if the scheme allows it) layer by layer, the way
+    /// beginners learn, explaining each step
+    Beginner {
+        /// The scramble to apply, e.g. "R U R' U'"
+        scramble: String,
+        /// Color for the cross, as a letter: Y W G B R or O
+        #[arg(long, default_value = "W")]
+        color: String,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Find the optimal EOLine (or EOCross) for a scramble (white top, green front, if the
+    /// scheme allows it): every edge oriented for F/B, plus the DF and DB edges (or whole cross)
+    Zz {
+        /// The scramble to apply, e.g. "R U R' U'"
+        scramble: String,
+        /// Color for the bottom, as a letter: Y W G B R or O
+        #[arg(long, default_value = "W")]
+        color: String,
+        /// Solve the whole cross, not just the line
+        #[arg(long)]
+        cross: bool,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Find the shortest way to solve any set of pieces (or just orient them) for a scramble
+    /// (white top, green front, if the scheme allows it), without turning the cube over
+    Mask {
+        /// The scramble to apply, e.g. "R U R' U'"
+        scramble: String,
+        /// What to solve, e.g. "solved: DF DR DB DL FR DFR; oriented: U*"; pieces are named by
+        /// their faces, a face with a star is every piece on that face, and "*" is everything
+        mask: String,
+        /// Give up if it takes more moves than this
+        #[arg(long, default_value_t = 10)]
+        max_moves: usize,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// Name the OLL or PLL case (and the AUF) left after applying an algorithm to a solved cube,
+    /// held with the U color of the scheme on top
+    Recognize {
+        /// The algorithm to apply, e.g. "R U R' U R U2 R'"; wide and slice moves are allowed
+        scramble: String,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
+    /// List the known algorithms for the case left after applying an algorithm to a solved
+    /// cube, held the same way as for the recognize command; or for a case by name, or every
+    /// algorithm in the set if neither is given
+    Algs {
+        /// The algorithm to apply, e.g. "R U R' U R U2 R'"; wide and slice moves are allowed
+        scramble: Option<String>,
+        /// Which algorithms to look through: OLL, PLL, COLL, ZBLL or F2L
+        #[arg(long, default_value = "OLL")]
+        set: String,
+        /// Name of the case to look up instead, e.g. 27 for OLL or T for PLL
+        #[arg(long)]
+        case: Option<String>,
+        /// More algorithms to use, in the same format as src/algs.txt; can be given more than
+        /// once
+        #[arg(long = "file")]
+        files: Vec<String>,
+        /// Color scheme of the cube; see the scramble command
+        #[arg(long, default_value = "western")]
+        scheme: String,
+    },
 }
 
 fn main() {
@@ -396,6 +1640,166 @@ fn main() {
 
     match &cli.command {
         Commands::Benchmark => big_suite(),
-        Commands::Scramble => scramble_things(),
+        Commands::Scramble {
+            scheme,
+            subset,
+            min_cross,
+            min_phase1,
+            min_solution,
+            max_solution,
+            seed,
+            count,
+            out,
+            format,
+            images,
+        } => scramble_things(
+            scheme,
+            subset,
+            Difficulty {
+                min_cross: *min_cross,
+                min_phase1: *min_phase1,
+                min_solution: *min_solution,
+                max_solution: *max_solution,
+            },
+            *seed,
+            *count,
+            ScrambleOutput {
+                out: out.as_deref(),
+                format,
+                images: images.as_deref(),
+            },
+        ),
+        Commands::RandomMoves {
+            length,
+            faces,
+            count,
+            seed,
+            solve,
+            scheme,
+        } => random_moves_things(*length, faces, *count, *seed, *solve, scheme),
+        Commands::Session {
+            file,
+            time,
+            penalty,
+            scramble,
+            timer,
+            export,
+            scheme,
+        } => session_things(
+            file,
+            time.as_deref(),
+            penalty,
+            scramble.as_deref(),
+            *timer,
+            export.as_deref(),
+            scheme,
+        ),
+        Commands::Import { file, csv } => import_things(file, csv.as_deref()),
+        Commands::Transform {
+            alg,
+            mirror,
+            rotate,
+        } => transform_things(alg, mirror.as_deref(), rotate.as_deref()),
+        Commands::Analyze { alg } => analyze_things(alg),
+        Commands::Show {
+            scramble,
+            no_color,
+            scheme,
+        } => show_things(scramble, *no_color, scheme),
+        Commands::Image {
+            scramble,
+            view,
+            out,
+            scheme,
+        } => image_things(scramble, view, out.as_deref(), scheme),
+        Commands::Cross { scramble, scheme } => cross_things(scramble, scheme),
+        Commands::Xcross {
+            scramble,
+            color,
+            slots,
+            scheme,
+        } => xcross_things(scramble, color, slots, scheme),
+        Commands::Cfop {
+            scramble,
+            color,
+            scheme,
+        } => cfop_things(scramble, color, scheme),
+        Commands::Roux {
+            scramble,
+            color,
+            scheme,
+        } => roux_things(scramble, color, scheme),
+        Commands::Playback {
+            scramble,
+            method,
+            no_color,
+            scheme,
+        } => playback_things(scramble, method, *no_color, scheme),
+        Commands::Beginner {
+            scramble,
+            color,
+            scheme,
+        } => beginner_things(scramble, color, scheme),
+        Commands::Zz {
+            scramble,
+            color,
+            cross,
+            scheme,
+        } => zz_things(scramble, color, *cross, scheme),
+        Commands::Mask {
+            scramble,
+            mask,
+            max_moves,
+            scheme,
+        } => mask_things(scramble, mask, *max_moves, scheme),
+        Commands::Recognize { scramble, scheme } => recognize_things(scramble, scheme),
+        Commands::Algs {
+            scramble,
+            set,
+            case,
+            files,
+            scheme,
+        } => algs_things(scramble.as_deref(), case.as_deref(), set, files, scheme),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shadow::MaskSpec;
+
+    use super::*;
+
+    #[test]
+    fn subset_hold() {
+        let scheme = ColorScheme::western();
+
+        // OLL and then PLL solves any last layer, which is all an ll scramble needs
+        let solve = |cube: &cube::Cube| {
+            let oll = last_layer::recognize_oll(cube).map_or(Vec::new(), |case| case.moves());
+            let oriented = cube.clone().apply_many(&oll);
+            let pll = match last_layer::recognize_pll(&oriented) {
+                Some(case) => case.moves(),
+                None => last_layer::auf_moves(last_layer::recognize_auf(&oriented).unwrap()),
+            };
+            Some([oll, pll].concat())
+        };
+
+        let kept = MaskSpec::try_from("solved: D* FL FR BL BR").unwrap();
+        let scrambles = scramble::scramble_batch(&scheme, Subset::LastLayer, 5, Some(1), 1, &solve);
+
+        for scramble in scrambles {
+            let cube = scramble_hold(&scheme, Subset::LastLayer).apply_many(&scramble.moves);
+
+            // the white cross and the middle layer are left alone, so only yellow is scrambled
+            assert_eq!(cube.d.cc, Facelet::White);
+            assert!(
+                kept.apply(cube).is_solved(),
+                "{}",
+                to_nice_str(&scramble.moves)
+            );
+        }
+
+        // full scrambles are still done the WCA way
+        assert_eq!(scramble_hold(&scheme, Subset::Full).u.cc, Facelet::White);
     }
 }
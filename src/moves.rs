@@ -78,6 +78,58 @@ pub fn invert(fms: &[FullMove]) -> Vec<FullMove> {
     out
 }
 
+/// The same moves with nothing wasted: turns of the same face are combined (and dropped if they
+/// come to nothing), and turns of opposite faces, which commute, always go in the order the
+/// solvers use -- B before F, L before R, D before U. So [crate::dfs_util::can_follow] holds all
+/// the way through, and the cube ends up the same.
+pub fn canonicalize(fms: &[FullMove]) -> Vec<FullMove> {
+    // runs of moves on one axis, as quarter turns of each face of the pair
+    let mut runs: Vec<([Dir; 2], [u8; 2])> = Vec::new();
+
+    for fm in fms {
+        let pair = match fm.dir {
+            Dir::B | Dir::F => [Dir::B, Dir::F],
+            Dir::L | Dir::R => [Dir::L, Dir::R],
+            Dir::D | Dir::U => [Dir::D, Dir::U],
+        };
+        let side = if pair[0] == fm.dir { 0 } else { 1 };
+        let quarters = match fm.amt {
+            Amt::One => 1,
+            Amt::Two => 2,
+            Amt::Rev => 3,
+        };
+
+        match runs.last_mut() {
+            Some((last, turns)) if *last == pair => {
+                turns[side] = (turns[side] + quarters) % 4;
+
+                // and if the run is gone, whatever was before it can join up with what's next
+                if *turns == [0, 0] {
+                    runs.pop();
+                }
+            }
+            _ => {
+                let mut turns = [0, 0];
+                turns[side] = quarters;
+                runs.push((pair, turns));
+            }
+        }
+    }
+
+    runs.into_iter()
+        .flat_map(|(pair, turns)| pair.into_iter().zip(turns))
+        .filter_map(|(dir, quarters)| {
+            let amt = match quarters {
+                1 => Amt::One,
+                2 => Amt::Two,
+                3 => Amt::Rev,
+                _ => return None,
+            };
+            Some(FullMove { dir, amt })
+        })
+        .collect()
+}
+
 pub fn to_nice_str(fms: &[FullMove]) -> String {
     let mut moves = fms.iter().copied();
 
@@ -221,6 +273,41 @@ impl<A: CanMove, B: CanMove> CanMove for (A, B) {
     }
 }
 
+/// Moves every element; handy for searching over a handful of states at once
+impl<A: CanMove> CanMove for Vec<A> {
+    fn r(self) -> Self {
+        self.into_iter().map(A::r).collect()
+    }
+
+    fn l(self) -> Self {
+        self.into_iter().map(A::l).collect()
+    }
+
+    fn u(self) -> Self {
+        self.into_iter().map(A::u).collect()
+    }
+
+    fn u_two(self) -> Self {
+        self.into_iter().map(A::u_two).collect()
+    }
+
+    fn d(self) -> Self {
+        self.into_iter().map(A::d).collect()
+    }
+
+    fn d_two(self) -> Self {
+        self.into_iter().map(A::d_two).collect()
+    }
+
+    fn b(self) -> Self {
+        self.into_iter().map(A::b).collect()
+    }
+
+    fn f(self) -> Self {
+        self.into_iter().map(A::f).collect()
+    }
+}
+
 pub trait CanMove: Sized {
     fn r(self) -> Self;
 
@@ -536,7 +623,7 @@ mod tests {
     use super::*;
 
     fn solved() -> Cube {
-        return Cube::make_solved(Facelet::White, Facelet::Blue);
+        Cube::make_solved(Facelet::White, Facelet::Blue)
     }
 
     #[test]
@@ -611,4 +698,32 @@ mod tests {
 
         moves_solved("R2 L R' L' R' F B F' B2 F2 B F2");
     }
+
+    #[test]
+    fn canonical_moves() {
+        let cases = [
+            ("", ""),
+            ("R U R' U'", "R U R' U'"),
+            ("R R", "R2"),
+            ("U U2", "U'"),
+            ("F F'", ""),
+            ("R U U' R", "R2"),
+            ("U D", "D U"),
+            ("R L' R", "L' R2"),
+            ("U D U' F", "D F"),
+            ("B2 F R L R' L' F'", "B2"),
+            ("F2 F' U R' U2 U2", "F U R'"),
+        ];
+
+        for (input, expected) in cases {
+            let moves = parse_many(input);
+            let canonical = canonicalize(&moves);
+            assert_eq!(to_nice_str(&canonical), expected, "{input}");
+
+            assert_eq!(solved().apply_many(&moves), solved().apply_many(&canonical));
+            assert!(canonical
+                .windows(2)
+                .all(|w| crate::dfs_util::can_follow(Some(w[0].dir), w[1].dir)));
+        }
+    }
 }
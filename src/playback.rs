@@ -0,0 +1,104 @@
+//! Module for playing a solution back one move at a time, keeping track of where each phase ends.
+//! The solvers just hand back a list of moves, which doesn't show much about how they got there;
+//! this is for watching a Thistlethwaite or Kociemba solve work its way down the subgroups.
+
+use crate::cube::Cube;
+use crate::moves::{ApplyMove, FullMove};
+
+/// One phase of a solution, like getting from G0 to G1
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Phase {
+    /// What the cube is in once the phase is done, like "G1" or "H1"
+    pub reached: String,
+    /// What's true of the cube once the phase is done, in words
+    pub invariant: String,
+    pub moves: Vec<FullMove>,
+}
+
+/// The cube after some number of moves of a solution
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Frame {
+    /// How many moves have been done; zero for the starting position
+    pub move_number: usize,
+    /// The move which was just done, if any
+    pub last_move: Option<FullMove>,
+    pub cube: Cube,
+    /// Indices of the phases which finished with this move. Usually there's at most one, but
+    /// a phase with no moves finishes at the same time as the one before it.
+    pub finished: Vec<usize>,
+}
+
+/// Does the phases one move at a time from the starting cube, giving the cube after each move
+/// (and before the first one)
+pub fn playback(start: &Cube, phases: &[Phase]) -> Vec<Frame> {
+    let mut frames = vec![Frame {
+        move_number: 0,
+        last_move: None,
+        cube: start.clone(),
+        finished: Vec::new(),
+    }];
+
+    for (i, phase) in phases.iter().enumerate() {
+        for &fm in &phase.moves {
+            let prev = frames.last().unwrap();
+
+            frames.push(Frame {
+                move_number: prev.move_number + 1,
+                last_move: Some(fm),
+                cube: prev.cube.clone().apply(fm),
+                finished: Vec::new(),
+            });
+        }
+
+        frames.last_mut().unwrap().finished.push(i);
+    }
+
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cube::Facelet;
+    use crate::moves::parse_many;
+
+    use super::*;
+
+    fn phase(reached: &str, moves: &str) -> Phase {
+        Phase {
+            reached: reached.to_string(),
+            invariant: String::new(),
+            moves: parse_many(moves),
+        }
+    }
+
+    #[test]
+    fn frames_and_boundaries() {
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+        let start = solved.clone().apply_many(&parse_many("R U F"));
+
+        let phases = [phase("A", "F'"), phase("B", ""), phase("C", "U' R'")];
+        let frames = playback(&start, &phases);
+
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0].cube, start);
+        assert_eq!(frames[0].last_move, None);
+        assert!(frames[0].finished.is_empty());
+
+        // the empty phase finishes along with the first one
+        assert_eq!(frames[1].finished, vec![0, 1]);
+        assert_eq!(
+            frames[1].cube,
+            solved.clone().apply_many(&parse_many("R U"))
+        );
+        assert!(frames[2].finished.is_empty());
+        assert_eq!(frames[3].finished, vec![2]);
+        assert_eq!(frames[3].move_number, 3);
+        assert_eq!(frames[3].last_move, parse_many("R'").first().copied());
+        assert_eq!(frames[3].cube, solved);
+
+        // nothing to do is still one frame, with every phase done at once
+        let frames = playback(&solved, &[phase("A", ""), phase("B", "")]);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].finished, vec![0, 1]);
+    }
+}
@@ -0,0 +1,238 @@
+//! Module for drawing a cube in the terminal, as the usual unfolded net:
+//!
+//! ```text
+//!        U U U
+//!        U U U
+//!        U U U
+//! L L L  F F F  R R R  B B B
+//! L L L  F F F  R R R  B B B
+//! L L L  F F F  R R R  B B B
+//!        D D D
+//!        D D D
+//!        D D D
+//! ```
+//!
+//! The plain rendering uses a letter per facelet; the colored one uses ANSI background colors
+//! instead. Either one works for masked cubes, where unknown facelets show up as gray.
+
+use std::fmt::{Display, Formatter};
+
+use crate::cube::{AmbigFacelet, Cube, Facelet};
+use crate::moves::Dir;
+
+/// Anything which can be drawn as one facelet of a net
+pub trait Sticker {
+    /// Letter to use when colors aren't available
+    fn letter(&self) -> char;
+
+    /// ANSI 256-color palette index to use as the background
+    fn ansi_color(&self) -> u8;
+
+    /// Hex color (like `#ffffff`) to use in images
+    fn hex_color(&self) -> &'static str;
+}
+
+impl Sticker for Facelet {
+    fn letter(&self) -> char {
+        match self {
+            Facelet::Yellow => 'Y',
+            Facelet::White => 'W',
+            Facelet::Green => 'G',
+            Facelet::Blue => 'B',
+            Facelet::Red => 'R',
+            Facelet::Orange => 'O',
+        }
+    }
+
+    fn ansi_color(&self) -> u8 {
+        match self {
+            Facelet::Yellow => 226,
+            Facelet::White => 231,
+            Facelet::Green => 34,
+            Facelet::Blue => 21,
+            Facelet::Red => 160,
+            Facelet::Orange => 208,
+        }
+    }
+
+    fn hex_color(&self) -> &'static str {
+        match self {
+            Facelet::Yellow => "#ffd500",
+            Facelet::White => "#ffffff",
+            Facelet::Green => "#009e60",
+            Facelet::Blue => "#0051ba",
+            Facelet::Red => "#c41e3a",
+            Facelet::Orange => "#ff5800",
+        }
+    }
+}
+
+impl Sticker for AmbigFacelet {
+    fn letter(&self) -> char {
+        match self {
+            AmbigFacelet::Yellow => 'Y',
+            AmbigFacelet::White => 'W',
+            AmbigFacelet::Green => 'G',
+            AmbigFacelet::Blue => 'B',
+            AmbigFacelet::Red => 'R',
+            AmbigFacelet::Orange => 'O',
+            AmbigFacelet::Unknown => '.',
+        }
+    }
+
+    fn ansi_color(&self) -> u8 {
+        match self {
+            AmbigFacelet::Yellow => 226,
+            AmbigFacelet::White => 231,
+            AmbigFacelet::Green => 34,
+            AmbigFacelet::Blue => 21,
+            AmbigFacelet::Red => 160,
+            AmbigFacelet::Orange => 208,
+            AmbigFacelet::Unknown => 244,
+        }
+    }
+
+    fn hex_color(&self) -> &'static str {
+        match self {
+            AmbigFacelet::Yellow => "#ffd500",
+            AmbigFacelet::White => "#ffffff",
+            AmbigFacelet::Green => "#009e60",
+            AmbigFacelet::Blue => "#0051ba",
+            AmbigFacelet::Red => "#c41e3a",
+            AmbigFacelet::Orange => "#ff5800",
+            AmbigFacelet::Unknown => "#808080",
+        }
+    }
+}
+
+/// Renders the cube as an unfolded net, one line per row of facelets. If `color` is set, each
+/// facelet is drawn as a colored block using ANSI escapes; otherwise each one is a letter.
+pub fn render_net<F: Sticker>(cube: &Cube<F>, color: bool) -> String {
+    // every facelet takes up two columns either way, so the layout is the same
+    let draw = |f: &F| {
+        if color {
+            format!("\x1b[48;5;{}m  \x1b[0m", f.ansi_color())
+        } else {
+            format!("{} ", f.letter())
+        }
+    };
+
+    // one face width, plus the gap between faces
+    let indent = " ".repeat(7);
+
+    let mut lines = Vec::with_capacity(9);
+
+    for row in 0..3 {
+        let cells: String = cube.face_grid(Dir::U)[row]
+            .iter()
+            .map(|f| draw(f))
+            .collect();
+        lines.push(format!("{indent}{cells}"));
+    }
+
+    for row in 0..3 {
+        let faces: Vec<String> = [Dir::L, Dir::F, Dir::R, Dir::B]
+            .iter()
+            .map(|&face| cube.face_grid(face)[row].iter().map(|f| draw(f)).collect())
+            .collect();
+        lines.push(faces.join(" "));
+    }
+
+    for row in 0..3 {
+        let cells: String = cube.face_grid(Dir::D)[row]
+            .iter()
+            .map(|f| draw(f))
+            .collect();
+        lines.push(format!("{indent}{cells}"));
+    }
+
+    lines
+        .iter()
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Displays the cube as a net of letters; use the alternate flag (`{:#}`) for ANSI colors.
+impl<F: Sticker> Display for Cube<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", render_net(self, f.alternate()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::moves::{parse_many, ApplyMove};
+    use crate::shadow::to_white_cross;
+
+    use super::*;
+
+    #[test]
+    fn solved_net() {
+        let cube = Cube::make_solved(Facelet::Green, Facelet::White);
+
+        let expected = [
+            "       W W W",
+            "       W W W",
+            "       W W W",
+            "O O O  G G G  R R R  B B B",
+            "O O O  G G G  R R R  B B B",
+            "O O O  G G G  R R R  B B B",
+            "       Y Y Y",
+            "       Y Y Y",
+            "       Y Y Y",
+        ]
+        .join("\n");
+
+        assert_eq!(cube.to_string(), expected);
+    }
+
+    #[test]
+    fn one_move_net() {
+        let cube = Cube::make_solved(Facelet::Green, Facelet::White).apply_many(&parse_many("R"));
+
+        let expected = [
+            "       W W G",
+            "       W W G",
+            "       W W G",
+            "O O O  G G Y  R R R  W B B",
+            "O O O  G G Y  R R R  W B B",
+            "O O O  G G Y  R R R  W B B",
+            "       Y Y B",
+            "       Y Y B",
+            "       Y Y B",
+        ]
+        .join("\n");
+
+        assert_eq!(cube.to_string(), expected);
+    }
+
+    #[test]
+    fn masked_net() {
+        let cube = to_white_cross(Cube::make_solved(Facelet::Green, Facelet::White));
+
+        let expected = [
+            "       . W .",
+            "       W W W",
+            "       . W .",
+            ". O .  . G .  . R .  . B .",
+            ". O .  . G .  . R .  . B .",
+            ". . .  . . .  . . .  . . .",
+            "       . . .",
+            "       . Y .",
+            "       . . .",
+        ]
+        .join("\n");
+
+        assert_eq!(cube.to_string(), expected);
+    }
+
+    #[test]
+    fn colored_net() {
+        let rendered = format!("{:#}", Cube::make_solved(Facelet::Green, Facelet::White));
+
+        assert_eq!(rendered.lines().count(), 9);
+        assert_eq!(rendered.matches("\x1b[48;5;34m").count(), 9);
+        assert!(!rendered.contains('G'));
+    }
+}
@@ -0,0 +1,479 @@
+//! Module for solving a cube with the Roux method: a 1x2x3 block on the left (first block), a
+//! matching one on the right (second block), then the last layer corners (CMLL), and finally the
+//! last six edges (LSE) with nothing but M and U.
+//!
+//! The blocks are solved optimally with face turns, keeping the centers where they are, so the
+//! M slice is never off. CMLL is done in two looks, orienting the corners with one of the OLL
+//! algorithms that only twist corners and then permuting them with a PLL algorithm; the edges
+//! don't matter yet, so this always works. LSE is solved optimally from a table of every
+//! position M and U can reach, which is small enough to just build.
+
+use std::fmt::{Display, Formatter};
+
+use ahash::HashMap;
+use std::collections::VecDeque;
+
+use crate::cube::{AmbigFacelet, ColorScheme, Cube, Facelet};
+use crate::dfs_util;
+use crate::heuristic_caches::{Heuristic, HeuristicCache};
+use crate::last_layer::{AUFS, OLL_ALGS, PLL_ALGS};
+use crate::moves::{parse_many, to_nice_str, Amt, ApplyMove, Dir, FullMove, ALL_DIRS};
+use crate::shadow::{to_block_square, to_cmll, Slot, ALL_SLOTS};
+use crate::solve::rotation_to_bottom;
+use crate::transform::{leftover_rotations, parse_alg, Axis, Rotation};
+
+type MaskedCube = Cube<AmbigFacelet>;
+
+/// The squares making up the first block, on the left
+const FIRST_BLOCK: [Slot; 2] = [Slot::FL, Slot::BL];
+
+/// One stage of a Roux solve. The moves are kept as written, since LSE needs M moves, which
+/// aren't face turns; use [parse_alg] to get face turns out.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct RouxStage {
+    pub name: String,
+    pub alg: String,
+}
+
+impl RouxStage {
+    /// Number of moves, counting M moves as one and whole-cube rotations as nothing
+    pub fn len(&self) -> usize {
+        self.alg
+            .split_whitespace()
+            .filter(|token| !token.starts_with(['x', 'y', 'z']))
+            .count()
+    }
+}
+
+/// A whole Roux solve; do the rotation (if any) first, to put the bottom color on the bottom,
+/// then the moves of each stage in order
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct RouxSolution {
+    pub rotation: Option<Rotation>,
+    pub stages: Vec<RouxStage>,
+}
+
+impl RouxSolution {
+    /// Every move of the solve, stage after stage, as written
+    pub fn alg(&self) -> String {
+        let algs: Vec<&str> = self
+            .stages
+            .iter()
+            .map(|stage| stage.alg.as_str())
+            .filter(|alg| !alg.is_empty())
+            .collect();
+
+        algs.join(" ")
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.iter().map(RouxStage::len).sum()
+    }
+}
+
+impl Display for RouxSolution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(rotation) = self.rotation {
+            writeln!(f, "Rotation: {rotation}")?;
+        }
+
+        for stage in &self.stages {
+            writeln!(f, "{} ({} moves): {}", stage.name, stage.len(), stage.alg)?;
+        }
+
+        write!(f, "Total: {} moves", self.len())
+    }
+}
+
+/// Heuristics for the blocks, plus the table for LSE. As with the CFOP caches, everything is
+/// recolored to the Western scheme (white on D, green in front) first, so one set of tables works
+/// for any bottom color.
+pub struct RouxCache {
+    // one for each square, in the order of ALL_SLOTS
+    squares: Vec<HeuristicCache<MaskedCube>>,
+    // each of LSE_MOVES, worked out ahead of time, since M moves are slow to do from scratch
+    lse_moves: Vec<FaceletSources>,
+    // optimal number of M and U moves to solve each LSE position
+    lse: HashMap<Cube, usize>,
+}
+
+impl RouxCache {
+    pub fn initialize() -> Self {
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+
+        // each square is only a corner and two edges, so these are tiny
+        let squares = ALL_SLOTS
+            .iter()
+            .map(|&slot| {
+                HeuristicCache::from_goal(to_block_square(solved.clone(), slot), &ALL_DIRS, &[])
+            })
+            .collect();
+
+        let identity = Cube::from_fn(|face, row, col| (face, row, col));
+        let lse_moves: Vec<FaceletSources> = LSE_MOVES
+            .iter()
+            .map(|token| lse_move(&identity, token))
+            .collect();
+
+        // a plain BFS; there are only a couple hundred thousand positions
+        let mut lse = HashMap::default();
+        let mut to_process = VecDeque::new();
+        to_process.push_back((solved, 0));
+
+        while let Some((pos, cost)) = to_process.pop_front() {
+            if lse.contains_key(&pos) {
+                continue;
+            }
+
+            for sources in &lse_moves {
+                to_process.push_back((permuted(&pos, sources), cost + 1));
+            }
+
+            lse.insert(pos, cost);
+        }
+
+        Self {
+            squares,
+            lse_moves,
+            lse,
+        }
+    }
+}
+
+struct BlocksHeuristic<'a> {
+    cache: &'a RouxCache,
+    squares: &'a [Slot],
+}
+
+impl<'a> Heuristic<Vec<MaskedCube>> for BlocksHeuristic<'a> {
+    fn evaluate(&self, state: &Vec<MaskedCube>) -> usize {
+        self.squares
+            .iter()
+            .zip(state.iter())
+            .map(|(slot, square)| {
+                let i = ALL_SLOTS.iter().position(|s| s == slot).unwrap();
+                self.cache.squares[i].evaluate(square)
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Solves the given squares optimally, keeping the ones that are already solved; the cube should
+/// be recolored already
+fn solve_squares(normalized: &Cube, squares: &[Slot], cache: &RouxCache) -> Vec<FullMove> {
+    let start: Vec<MaskedCube> = squares
+        .iter()
+        .map(|&slot| to_block_square(normalized.clone(), slot))
+        .collect();
+
+    // first block is rarely more than 8 moves, and a square of the second block is about the
+    // same; this leaves a little room
+    const MAX_FUEL: usize = 14;
+
+    let heuristic = BlocksHeuristic { cache, squares };
+
+    dfs_util::solve(
+        start,
+        &ALL_DIRS,
+        &[],
+        |state: &Vec<MaskedCube>| state.iter().all(|c| c.is_solved()),
+        &heuristic,
+        MAX_FUEL,
+    )
+}
+
+fn corners_oriented(cube: &Cube) -> bool {
+    [&cube.u.bl, &cube.u.br, &cube.u.fl, &cube.u.fr]
+        .iter()
+        .all(|&corner| corner == &cube.u.cc)
+}
+
+fn auf_str(auf: Option<Amt>) -> &'static str {
+    match auf {
+        None => "",
+        Some(Amt::One) => "U",
+        Some(Amt::Two) => "U2",
+        Some(Amt::Rev) => "U'",
+    }
+}
+
+/// Joins up bits of algorithm, skipping any empty ones
+fn join(parts: &[&str]) -> String {
+    let parts: Vec<&str> = parts.iter().copied().filter(|p| !p.is_empty()).collect();
+    parts.join(" ")
+}
+
+/// Adds whatever rotations are needed to get the cube back to how it was held at the start; so
+/// each stage leaves the centers where the next stage expects them, even once they're all put
+/// together
+fn undo_leftovers(alg: String) -> String {
+    let undo: Vec<String> = leftover_rotations(&alg)
+        .iter()
+        .rev()
+        .map(|rotation| rotation.inverse().to_string())
+        .collect();
+
+    join(&[&alg, &undo.join(" ")])
+}
+
+/// Two-look CMLL; gives a name for what was done, and the algorithm
+fn solve_cmll(cube: &Cube) -> (String, String) {
+    if to_cmll(cube.clone()).is_solved() {
+        return ("CMLL skip".to_string(), String::new());
+    }
+
+    // OLL 21 through 27 are the cases with the edges oriented, so their algorithms cover every
+    // way the corners can be twisted
+    let (orient_name, orient) = if corners_oriented(cube) {
+        (None, String::new())
+    } else {
+        AUFS.iter()
+            .flat_map(|&auf| (21..=27).map(move |number| (auf, number)))
+            .map(|(auf, number)| {
+                let (name, alg) = OLL_ALGS[number - 1];
+                (
+                    Some(format!("OLL {number} {name}")),
+                    undo_leftovers(join(&[auf_str(auf), alg])),
+                )
+            })
+            .filter(|(_, alg)| corners_oriented(&cube.clone().apply_many(&parse_alg(alg))))
+            .min_by_key(|(_, alg)| alg.split_whitespace().count())
+            .expect("Some OLL algorithm should orient the corners")
+    };
+
+    let oriented = cube.clone().apply_many(&parse_alg(&orient));
+
+    let (permute_name, permute) = AUFS
+        .iter()
+        .flat_map(|&pre| AUFS.iter().map(move |&post| (pre, post)))
+        .flat_map(|(pre, post)| {
+            let plls = PLL_ALGS
+                .iter()
+                .map(move |(name, alg)| (Some(format!("{name}-perm")), pre, *alg, post));
+            // maybe the corners just need an AUF
+            std::iter::once((None, pre, "", post)).chain(plls)
+        })
+        .map(|(name, pre, alg, post)| (name, join(&[auf_str(pre), alg, auf_str(post)])))
+        .filter(|(_, alg)| to_cmll(oriented.clone().apply_many(&parse_alg(alg))).is_solved())
+        .min_by_key(|(_, alg)| alg.split_whitespace().count())
+        .expect("Some PLL algorithm should permute the corners");
+
+    let names: Vec<String> = [orient_name, permute_name].into_iter().flatten().collect();
+    let name = if names.is_empty() {
+        "CMLL".to_string()
+    } else {
+        format!("CMLL ({})", names.join(", then "))
+    };
+
+    (name, join(&[&orient, &undo_leftovers(permute)]))
+}
+
+const LSE_MOVES: [&str; 6] = ["U", "U2", "U'", "M", "M2", "M'"];
+
+/// For each facelet, the face and place on its grid (see [Cube::face_grid]) it comes from after
+/// some move
+type FaceletSources = Cube<(Dir, usize, usize)>;
+
+fn permuted(cube: &Cube, sources: &FaceletSources) -> Cube {
+    Cube::from_fn(|face, row, col| {
+        let &(face, row, col) = sources.face_grid(face)[row][col];
+        cube.face_grid(face)[row][col].clone()
+    })
+}
+
+/// Does one of [LSE_MOVES] to the cube. Unlike [parse_alg], this really turns the M slice,
+/// centers and all, rather than turning R and L and leaving the cube rotated.
+fn lse_move<F: Clone>(cube: &Cube<F>, token: &str) -> Cube<F> {
+    let (face, amt) = token.split_at(1);
+    let times = match amt {
+        "" => 1,
+        "2" => 2,
+        _ => 3,
+    };
+
+    let mut out = cube.clone();
+
+    for _ in 0..times {
+        out = if face == "U" {
+            out.apply_many(&parse_many("U"))
+        } else {
+            // M turns like L, so that's R L' and then the whole cube like L
+            out.apply_many(&parse_many("R L'")).rotated(Rotation {
+                axis: Axis::X,
+                amt: Amt::Rev,
+            })
+        };
+    }
+
+    out
+}
+
+/// Optimal LSE, from the table; the blocks and corners should already be solved
+fn solve_lse(cube: &Cube, cache: &RouxCache) -> String {
+    let mut pos = cube.recolored(&ColorScheme::western());
+    let mut dist = *cache
+        .lse
+        .get(&pos)
+        .expect("Only the last six edges should be left for LSE");
+
+    let mut out = Vec::with_capacity(dist);
+
+    while dist > 0 {
+        // some move has to get one step closer, since that's how the table was built
+        let (token, next) = LSE_MOVES
+            .iter()
+            .zip(&cache.lse_moves)
+            .map(|(&token, sources)| (token, permuted(&pos, sources)))
+            .find(|(_, next)| cache.lse.get(next) == Some(&(dist - 1)))
+            .unwrap();
+
+        out.push(token);
+        pos = next;
+        dist -= 1;
+    }
+
+    out.join(" ")
+}
+
+/// Solves the cube with Roux, with the blocks on the given color
+pub fn solve_roux(cube: &Cube, color: &Facelet, cache: &RouxCache) -> RouxSolution {
+    let rotation = rotation_to_bottom(cube, color);
+
+    let mut held = match rotation {
+        Some(rotation) => cube.rotated(rotation),
+        None => cube.clone(),
+    };
+
+    let mut stages = Vec::new();
+
+    let moves = solve_squares(
+        &held.recolored(&ColorScheme::western()),
+        &FIRST_BLOCK,
+        cache,
+    );
+    held = held.apply_many(&moves);
+    stages.push(RouxStage {
+        name: "First block".to_string(),
+        alg: to_nice_str(&moves),
+    });
+
+    // the second block goes in a square at a time, whichever is quicker first; doing it all at
+    // once is a much deeper search, and this is how people do it anyway
+    let mut second_block = Vec::new();
+    let mut done = FIRST_BLOCK.to_vec();
+
+    while done.len() < ALL_SLOTS.len() {
+        let normalized = held.recolored(&ColorScheme::western());
+
+        let (slot, moves) = [Slot::FR, Slot::BR]
+            .into_iter()
+            .filter(|slot| !done.contains(slot))
+            .map(|slot| {
+                let squares = [done.clone(), vec![slot]].concat();
+                (slot, solve_squares(&normalized, &squares, cache))
+            })
+            .min_by_key(|(_, moves)| moves.len())
+            .unwrap();
+
+        done.push(slot);
+        held = held.apply_many(&moves);
+        second_block.extend(moves);
+    }
+
+    stages.push(RouxStage {
+        name: "Second block".to_string(),
+        alg: to_nice_str(&second_block),
+    });
+
+    let (name, alg) = solve_cmll(&held);
+    held = held.apply_many(&parse_alg(&alg));
+    assert!(to_cmll(held.clone()).is_solved());
+    stages.push(RouxStage { name, alg });
+
+    let alg = solve_lse(&held, cache);
+    held = held.apply_many(&parse_alg(&alg));
+    assert!(held.is_solved(), "Cube should be solved, that's the point");
+    stages.push(RouxStage {
+        name: "LSE".to_string(),
+        alg,
+    });
+
+    RouxSolution { rotation, stages }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shadow::to_cross_and_pairs;
+
+    use super::*;
+
+    #[test]
+    fn m_moves() {
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+
+        // M takes the F center down to D, like L does
+        let turned = lse_move(&solved, "M");
+        assert_eq!(turned.d.cc, Facelet::Green);
+        assert_eq!(turned.l, solved.l);
+        assert_eq!(turned.r, solved.r);
+
+        assert_eq!(lse_move(&turned, "M'"), solved);
+        assert_eq!(lse_move(&lse_move(&solved, "M2"), "M2"), solved);
+    }
+
+    #[test]
+    fn solves_and_splits_stages() {
+        let cache = RouxCache::initialize();
+
+        let cube = Cube::make_solved(Facelet::Green, Facelet::White)
+            .apply_many(&parse_many("R U2 F' L D2 B R' U F2 L' D B2 U'"));
+
+        let sol = solve_roux(&cube, &Facelet::White, &cache);
+
+        let names: Vec<&str> = sol.stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names.len(), 4);
+        assert_eq!(names[0], "First block");
+        assert_eq!(names[1], "Second block");
+        assert!(names[2].starts_with("CMLL"));
+        assert_eq!(names[3], "LSE");
+
+        // LSE is only M and U
+        assert!(sol.stages[3]
+            .alg
+            .split_whitespace()
+            .all(|token| token.starts_with(['M', 'U'])));
+
+        let held = cube.rotated(sol.rotation.unwrap());
+        assert!(held.apply_many(&parse_alg(&sol.alg())).is_solved());
+    }
+
+    #[test]
+    fn only_lse_left() {
+        let cache = RouxCache::initialize();
+
+        let cube = Cube::make_solved(Facelet::Green, Facelet::Yellow)
+            .apply_many(&parse_alg("M' U M U2 M' U M"));
+
+        let sol = solve_roux(&cube, &Facelet::White, &cache);
+
+        assert!(sol.stages[..3].iter().all(|s| s.len() == 0));
+        assert!(sol.stages[3].len() <= 7);
+        assert!(cube.apply_many(&parse_alg(&sol.alg())).is_solved());
+    }
+
+    #[test]
+    fn cmll_keeps_blocks() {
+        // a sune and then a T perm; the blocks are fine, the corners aren't
+        let cube = Cube::make_solved(Facelet::Green, Facelet::Yellow).apply_many(&parse_alg(
+            "R U R' U R U2 R' R U R' U' R' F R2 U' R' U' R U R' F'",
+        ));
+
+        let (name, alg) = solve_cmll(&cube);
+        assert!(name.starts_with("CMLL"));
+
+        let done = cube.apply_many(&parse_alg(&alg));
+        assert!(to_cmll(done.clone()).is_solved());
+        assert!(to_cross_and_pairs(done, &ALL_SLOTS).is_solved());
+    }
+}
@@ -2,40 +2,50 @@ use rand::prelude::*;
 
 use crate::corner_orientation_state::{CornerOrientation, CornerOrientationState};
 use crate::corner_position_state::{CornerCubelet, CubeCornerPositions};
-use crate::cube::{Cube, Facelet, CORNERS, EDGES};
+use crate::cube::{ColorScheme, Cube, Facelet};
+use crate::dfs_util::can_follow;
 use crate::edge_orientation_state::EdgeOrientationState;
 use crate::edge_position_state::{CubeEdgePositions, SideCubelet};
+use crate::generators::{THREE_GEN, TWO_GEN};
+use crate::moves::{canonicalize, invert, ApplyMove, Dir, FullMove, ALL_AMTS};
+use crate::solve::{best_cross, solve_all_crosses, CrossCache};
 use crate::total_position_state::CubePositions;
 
 /// Gives a random solvable scramble of a cube with no restrictions, except the rotation is fixed
-/// (green from and yellow top).
-pub fn scramble_any() -> Cube {
+/// to the way the color scheme is described (for the Western scheme, green front and yellow top).
+#[allow(unused)]
+pub fn scramble_with_scheme(scheme: &ColorScheme) -> Cube {
+    scramble_with_rng(scheme, &mut thread_rng())
+}
+
+/// Same as [scramble_with_scheme], but with randomness from the given generator; so a seeded
+/// generator always gives the same cube. (For something like `StdRng`, that's only promised for
+/// the same version of `rand`.)
+pub fn scramble_with_rng<R: Rng>(scheme: &ColorScheme, rng: &mut R) -> Cube {
     use CornerCubelet::*;
     use SideCubelet::*;
 
-    let mut rng = thread_rng();
+    // every coordinate gets a uniformly random number, which is then turned back into a state;
+    // the last piece of each kind is whatever makes the cube solvable, so every solvable state
+    // is exactly as likely as every other
 
-    // simply; put each edge and each corner in randomly
-    // if the position is unsolvable, swap two edges
-    // if the edge orientation is unsolvable, flip the last edge placed
-    // if the corner orientation is unsolvable
-
-    // starting with position ...
-    let mut corners: Vec<CornerCubelet> = vec![FUL, FUR, BUL, BUR, FDL, FDR, BDL, BDR];
-    corners.shuffle(&mut rng);
-    let corner_pos = make_corner_pos(&corners);
+    // starting with position; corners are anything, then edges have to match their parity, so
+    // pick from half of the numbers and let the last digit settle it
+    let corners = from_rank(
+        &[FUL, FUR, BUL, BUR, FDL, FDR, BDL, BDR],
+        rng.gen_range(0..FACT_8),
+    );
 
-    let mut edges: Vec<SideCubelet> = vec![UF, UR, UB, UL, FL, FR, BL, BR, DF, DR, DB, DL];
-    edges.shuffle(&mut rng);
-    let edge_pos = make_edge_pos(&edges);
+    let edge_rank = 2 * rng.gen_range(0..FACT_12 / 2);
+    let edge_list = [UF, UR, UB, UL, FL, FR, BL, BR, DF, DR, DB, DL];
 
     let mut total_pos = CubePositions {
-        edges: edge_pos,
-        corners: corner_pos,
+        edges: make_edge_pos(&from_rank(&edge_list, edge_rank)),
+        corners: make_corner_pos(&corners),
     };
 
     if !total_pos.directly_solvable() {
-        std::mem::swap(&mut total_pos.edges.uf, &mut total_pos.edges.ur);
+        total_pos.edges = make_edge_pos(&from_rank(&edge_list, edge_rank + 1));
     }
 
     assert!(
@@ -43,60 +53,64 @@ pub fn scramble_any() -> Cube {
         "Total position should be solvable"
     );
 
-    // then edge orientation ...
+    // then edge orientation; one bit for each of the first eleven edges ...
+    let flips: u32 = rng.gen_range(0..1 << 11);
+    let flip = |i: u32| flips & (1 << i) != 0;
+
     let mut edge_orientation = EdgeOrientationState {
-        uf: rng.gen_bool(0.5),
-        ub: rng.gen_bool(0.5),
-        ul: rng.gen_bool(0.5),
-        ur: rng.gen_bool(0.5),
-        fl: rng.gen_bool(0.5),
-        fr: rng.gen_bool(0.5),
-        bl: rng.gen_bool(0.5),
-        br: rng.gen_bool(0.5),
-        df: rng.gen_bool(0.5),
-        db: rng.gen_bool(0.5),
-        dl: rng.gen_bool(0.5),
-        dr: rng.gen_bool(0.5),
+        uf: flip(0),
+        ub: flip(1),
+        ul: flip(2),
+        ur: flip(3),
+        fl: flip(4),
+        fr: flip(5),
+        bl: flip(6),
+        br: flip(7),
+        df: flip(8),
+        db: flip(9),
+        dl: flip(10),
+        dr: false,
     };
 
     if !edge_orientation.is_solvable() {
-        edge_orientation.uf = !edge_orientation.uf;
+        edge_orientation.dr = true;
     }
 
     assert!(edge_orientation.is_solvable());
 
-    // then corner orientation ...
-    let mut rand_orientation = || {
-        let p: f64 = rng.gen_range(0.0..1.0);
-        if p < 0.333 {
-            CornerOrientation::Good
-        } else if p < 0.667 {
-            CornerOrientation::CW
-        } else {
-            CornerOrientation::CCW
-        }
+    // then corner orientation; one base-3 digit for each of the first seven corners ...
+    let mut twists: u32 = rng.gen_range(0..3_u32.pow(7));
+    let mut twist = || {
+        let out = match twists % 3 {
+            0 => CornerOrientation::Good,
+            1 => CornerOrientation::CW,
+            _ => CornerOrientation::CCW,
+        };
+        twists /= 3;
+        out
     };
 
     let mut corner_orientation = CornerOrientationState {
-        ful: rand_orientation(),
-        fur: rand_orientation(),
-        fdl: rand_orientation(),
-        fdr: rand_orientation(),
-        bul: rand_orientation(),
-        bur: rand_orientation(),
-        bdl: rand_orientation(),
-        bdr: rand_orientation(),
+        ful: twist(),
+        fur: twist(),
+        fdl: twist(),
+        fdr: twist(),
+        bul: twist(),
+        bur: twist(),
+        bdl: twist(),
+        bdr: CornerOrientation::Good,
     };
 
     for _ in 0..3 {
         if !corner_orientation.is_solvable() {
-            corner_orientation.ful = corner_orientation.ful + CornerOrientation::CW;
+            corner_orientation.bdr = corner_orientation.bdr + CornerOrientation::CW;
         }
     }
 
     assert!(corner_orientation.is_solvable());
 
     make_cube(
+        scheme,
         total_pos.edges,
         total_pos.corners,
         edge_orientation,
@@ -104,7 +118,507 @@ pub fn scramble_any() -> Cube {
     )
 }
 
-fn make_edge_pos(edges: &Vec<SideCubelet>) -> CubeEdgePositions {
+/// Kinds of scramble, for practicing one part of a solve. Each one leaves everything outside of
+/// it solved.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Subset {
+    /// Anything at all
+    Full,
+    /// Only the last layer is scrambled
+    LastLayer,
+    /// Everything but the cross
+    F2l,
+    /// Anything reachable with R and U
+    TwoGen,
+    /// Anything reachable with R, U and F
+    ThreeGen,
+    /// Only the edges; the corners stay solved
+    EdgesOnly,
+    /// Only the corners; the edges stay solved
+    CornersOnly,
+    /// Anything reachable with L, R and half turns; that is, H1 from the Kociemba solver
+    Domino,
+}
+
+pub const ALL_SUBSETS: [Subset; 8] = [
+    Subset::Full,
+    Subset::LastLayer,
+    Subset::F2l,
+    Subset::TwoGen,
+    Subset::ThreeGen,
+    Subset::EdgesOnly,
+    Subset::CornersOnly,
+    Subset::Domino,
+];
+
+impl Subset {
+    fn name(self) -> &'static str {
+        match self {
+            Subset::Full => "full",
+            Subset::LastLayer => "ll",
+            Subset::F2l => "f2l",
+            Subset::TwoGen => "2gen",
+            Subset::ThreeGen => "3gen",
+            Subset::EdgesOnly => "edges",
+            Subset::CornersOnly => "corners",
+            Subset::Domino => "domino",
+        }
+    }
+
+    /// Whether the subset always leaves the D cross solved, so the easiest cross is never more
+    /// than zero moves
+    pub fn keeps_cross(self) -> bool {
+        matches!(self, Subset::LastLayer | Subset::F2l | Subset::CornersOnly)
+    }
+
+    /// For the subsets made of just a few faces, which faces those are; a scramble for one of
+    /// these should only turn its own faces
+    pub fn generators(self) -> Option<&'static [Dir]> {
+        match self {
+            Subset::TwoGen => Some(&TWO_GEN),
+            Subset::ThreeGen => Some(&THREE_GEN),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Subset {
+    type Error = &'a str;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        ALL_SUBSETS
+            .into_iter()
+            .find(|subset| subset.name().eq_ignore_ascii_case(value))
+            .ok_or(value)
+    }
+}
+
+impl std::fmt::Display for Subset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// positions, in the order make_corner_pos and make_edge_pos take them
+const CORNER_ORDER: [CornerCubelet; 8] = [
+    CornerCubelet::FUL,
+    CornerCubelet::FUR,
+    CornerCubelet::BUL,
+    CornerCubelet::BUR,
+    CornerCubelet::FDL,
+    CornerCubelet::FDR,
+    CornerCubelet::BDL,
+    CornerCubelet::BDR,
+];
+
+const EDGE_ORDER: [SideCubelet; 12] = [
+    SideCubelet::UF,
+    SideCubelet::UR,
+    SideCubelet::UB,
+    SideCubelet::UL,
+    SideCubelet::FL,
+    SideCubelet::FR,
+    SideCubelet::BL,
+    SideCubelet::BR,
+    SideCubelet::DF,
+    SideCubelet::DR,
+    SideCubelet::DB,
+    SideCubelet::DL,
+];
+
+const ALL_CORNERS: [usize; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+const ALL_EDGES: [usize; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const TOP_PIECES: [usize; 4] = [0, 1, 2, 3];
+// everything but the D edges
+const NON_CROSS_EDGES: [usize; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+// UF, UB, DF and DB, which H1 keeps in the M slice, and then the rest
+const M_SLICE_EDGES: [usize; 4] = [0, 2, 8, 10];
+const NON_M_SLICE_EDGES: [usize; 8] = [1, 3, 4, 5, 6, 7, 9, 11];
+
+/// How many moves to do for the subsets which are made by turning the cube randomly; plenty to
+/// be as good as random
+const RANDOM_MOVES: usize = 200;
+
+fn twist_at(state: &mut CornerOrientationState, i: usize) -> &mut CornerOrientation {
+    match i {
+        0 => &mut state.ful,
+        1 => &mut state.fur,
+        2 => &mut state.bul,
+        3 => &mut state.bur,
+        4 => &mut state.fdl,
+        5 => &mut state.fdr,
+        6 => &mut state.bdl,
+        _ => &mut state.bdr,
+    }
+}
+
+fn flip_at(state: &mut EdgeOrientationState, i: usize) -> &mut bool {
+    match i {
+        0 => &mut state.uf,
+        1 => &mut state.ur,
+        2 => &mut state.ub,
+        3 => &mut state.ul,
+        4 => &mut state.fl,
+        5 => &mut state.fr,
+        6 => &mut state.bl,
+        7 => &mut state.br,
+        8 => &mut state.df,
+        9 => &mut state.dr,
+        10 => &mut state.db,
+        _ => &mut state.dl,
+    }
+}
+
+/// Gives a random solvable cube from the subset; see [scramble_with_rng]
+pub fn scramble_subset<R: Rng>(scheme: &ColorScheme, subset: Subset, rng: &mut R) -> Cube {
+    match subset {
+        Subset::Full => scramble_with_rng(scheme, rng),
+        Subset::LastLayer => scramble_pieces(
+            scheme,
+            rng,
+            &[&TOP_PIECES],
+            &[&TOP_PIECES],
+            &TOP_PIECES,
+            &TOP_PIECES,
+        ),
+        Subset::F2l => scramble_pieces(
+            scheme,
+            rng,
+            &[&ALL_CORNERS],
+            &[&NON_CROSS_EDGES],
+            &ALL_CORNERS,
+            &NON_CROSS_EDGES,
+        ),
+        Subset::EdgesOnly => scramble_pieces(scheme, rng, &[], &[&ALL_EDGES], &[], &ALL_EDGES),
+        Subset::CornersOnly => {
+            scramble_pieces(scheme, rng, &[&ALL_CORNERS], &[], &ALL_CORNERS, &[])
+        }
+        Subset::Domino => scramble_pieces(
+            scheme,
+            rng,
+            &[&ALL_CORNERS],
+            &[&M_SLICE_EDGES, &NON_M_SLICE_EDGES],
+            &[],
+            &[],
+        ),
+        // working out which states these can reach is a lot harder, so just turn the cube a
+        // lot instead
+        Subset::TwoGen => scramble_moves(scheme, rng, &TWO_GEN),
+        Subset::ThreeGen => scramble_moves(scheme, rng, &THREE_GEN),
+    }
+}
+
+/// Random state where the pieces in each group (given as positions; see [CORNER_ORDER] and
+/// [EDGE_ORDER]) are shuffled among themselves, and only the pieces in the twisted and flipped
+/// positions can be out of orientation. Everything else stays solved.
+fn scramble_pieces<R: Rng>(
+    scheme: &ColorScheme,
+    rng: &mut R,
+    corner_groups: &[&[usize]],
+    edge_groups: &[&[usize]],
+    twisted: &[usize],
+    flipped: &[usize],
+) -> Cube {
+    fn shuffle_groups<T: Clone, R: Rng>(pieces: &mut [T], groups: &[&[usize]], rng: &mut R) {
+        for group in groups {
+            let mut picked: Vec<T> = group.iter().map(|&i| pieces[i].clone()).collect();
+            picked.shuffle(rng);
+
+            for (&i, piece) in group.iter().zip(picked) {
+                pieces[i] = piece;
+            }
+        }
+    }
+
+    let mut corners = CORNER_ORDER.to_vec();
+    shuffle_groups(&mut corners, corner_groups, rng);
+
+    let mut edges = EDGE_ORDER.to_vec();
+    shuffle_groups(&mut edges, edge_groups, rng);
+
+    // if the parity is off, swapping two pieces in the same group fixes it
+    let shuffled = CubePositions {
+        edges: make_edge_pos(&edges),
+        corners: make_corner_pos(&corners),
+    };
+
+    if !shuffled.directly_solvable() {
+        let edge_group = edge_groups.iter().find(|group| group.len() >= 2);
+        let corner_group = corner_groups.iter().find(|group| group.len() >= 2);
+
+        match (edge_group, corner_group) {
+            (Some(group), _) => edges.swap(group[0], group[1]),
+            (None, Some(group)) => corners.swap(group[0], group[1]),
+            (None, None) => panic!("Can't fix parity without at least two pieces to swap"),
+        }
+    }
+
+    let total_pos = CubePositions {
+        edges: make_edge_pos(&edges),
+        corners: make_corner_pos(&corners),
+    };
+
+    assert!(
+        total_pos.directly_solvable(),
+        "Total position should be solvable"
+    );
+
+    // then orientation, where the last piece makes up the difference, as usual
+    let mut edge_orientation = EdgeOrientationState::make_solved();
+    for &i in flipped {
+        *flip_at(&mut edge_orientation, i) = rng.gen_bool(0.5);
+    }
+
+    if let Some(&last) = flipped.last() {
+        if !edge_orientation.is_solvable() {
+            let flip = flip_at(&mut edge_orientation, last);
+            *flip = !*flip;
+        }
+    }
+
+    assert!(edge_orientation.is_solvable());
+
+    let mut corner_orientation = CornerOrientationState::solved();
+    for &i in twisted {
+        *twist_at(&mut corner_orientation, i) = match rng.gen_range(0..3) {
+            0 => CornerOrientation::Good,
+            1 => CornerOrientation::CW,
+            _ => CornerOrientation::CCW,
+        };
+    }
+
+    if let Some(&last) = twisted.last() {
+        for _ in 0..3 {
+            if !corner_orientation.is_solvable() {
+                let twist = twist_at(&mut corner_orientation, last);
+                *twist = *twist + CornerOrientation::CW;
+            }
+        }
+    }
+
+    assert!(corner_orientation.is_solvable());
+
+    make_cube(
+        scheme,
+        total_pos.edges,
+        total_pos.corners,
+        edge_orientation,
+        corner_orientation,
+    )
+}
+
+/// Turns a solved cube randomly, only using the given faces
+fn scramble_moves<R: Rng>(scheme: &ColorScheme, rng: &mut R, dirs: &[Dir]) -> Cube {
+    let mut cube = Cube::make_solved_with(scheme, scheme.f.clone(), scheme.u.clone());
+
+    for _ in 0..RANDOM_MOVES {
+        let dir = *dirs.choose(rng).unwrap();
+        let amt = *ALL_AMTS.choose(rng).unwrap();
+        cube = cube.apply(FullMove { dir, amt });
+    }
+
+    cube
+}
+
+/// Old-style random-move scramble: `length` moves, each on one of the given faces. Each move is
+/// one the solvers could make after the one before it (see [crate::dfs_util::can_follow]), so
+/// nothing cancels and nothing can be shortened by [canonicalize]. Panics if the faces run out of
+/// moves before the length does, like with just U and D.
+pub fn random_moves<R: Rng>(dirs: &[Dir], length: usize, rng: &mut R) -> Vec<FullMove> {
+    let mut moves: Vec<FullMove> = Vec::with_capacity(length);
+
+    for _ in 0..length {
+        let last = moves.last().map(|fm| fm.dir);
+        let options: Vec<Dir> = dirs
+            .iter()
+            .copied()
+            .filter(|&dir| can_follow(last, dir))
+            .collect();
+
+        let dir = *options
+            .choose(rng)
+            .unwrap_or_else(|| panic!("Can't make {length} moves out of just {dirs:?}"));
+        let amt = *ALL_AMTS.choose(rng).unwrap();
+
+        moves.push(FullMove { dir, amt });
+    }
+
+    moves
+}
+
+/// A scramble: the moves, and the cube they give (from solved, held the way the scheme says)
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Scramble {
+    pub cube: Cube,
+    pub moves: Vec<FullMove>,
+}
+
+impl Scramble {
+    /// Whether the moves really do give the cube, starting from solved
+    pub fn is_valid(&self, scheme: &ColorScheme) -> bool {
+        let solved = Cube::make_solved_with(scheme, scheme.f.clone(), scheme.u.clone());
+        solved.apply_many(&self.moves) == self.cube
+    }
+}
+
+/// Lower limits on how hard a scramble is; anything easier gets thrown out and another one made
+/// in its place, the way the official scramblers throw out states too close to solved. Zero
+/// means no limit, so the default lets everything through.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Difficulty {
+    /// Fewest moves for the easiest cross, on any color
+    pub min_cross: usize,
+    /// Fewest moves for the first Kociemba phase (getting to H1)
+    pub min_phase1: usize,
+    /// Fewest moves in the solution the scrambler finds, which is also how long the scramble is.
+    /// For most subsets that's the two-phase Kociemba solution, which isn't always optimal; so
+    /// this doesn't promise the cube is really that far from solved.
+    pub min_solution: usize,
+    /// Most moves in that same solution; this one is an upper limit, but zero still means none
+    pub max_solution: usize,
+}
+
+impl Difficulty {
+    /// Solves the cube with `phase1` and then `phase2`, unless it's too easy, in which case it's
+    /// None. The cross goes first since it's the cheapest, and each phase is only done if
+    /// everything before it was hard enough.
+    pub fn solve<P1, P2>(
+        &self,
+        cube: &Cube,
+        cross: &CrossCache,
+        phase1: P1,
+        phase2: P2,
+    ) -> Option<Vec<FullMove>>
+    where
+        P1: Fn(&Cube) -> Vec<FullMove>,
+        P2: Fn(&Cube) -> Vec<FullMove>,
+    {
+        if self.min_cross > 0 {
+            let crosses = solve_all_crosses(cube, cross);
+            if best_cross(&crosses).moves.len() < self.min_cross {
+                return None;
+            }
+        }
+
+        let mut moves = phase1(cube);
+        if moves.len() < self.min_phase1 {
+            return None;
+        }
+
+        let halfway = cube.clone().apply_many(&moves);
+        moves.extend(phase2(&halfway));
+
+        // the phases can overlap a little where they meet
+        let moves = canonicalize(&moves);
+
+        if moves.len() < self.min_solution
+            || (self.max_solution > 0 && moves.len() > self.max_solution)
+        {
+            return None;
+        }
+
+        Some(moves)
+    }
+}
+
+/// How many cubes to try for one scramble before giving up on the difficulty
+const MAX_ATTEMPTS: usize = 10_000;
+
+/// Makes `count` random-state scrambles from the subset, split between `threads` threads. The
+/// moves come from undoing whatever `solve` does, cleaned up with [canonicalize], so the threads
+/// all share one solver (and its cache); if `solve` gives None, the cube is thrown out and
+/// another one made. With a seed, each scramble gets a generator of its own, seeded with the
+/// next number from one generator seeded with `seed`; so the same seed always gives the same
+/// scrambles in the same order, however many threads there are, and nearby seeds don't share any.
+pub fn scramble_batch<Solve: Fn(&Cube) -> Option<Vec<FullMove>> + Sync>(
+    scheme: &ColorScheme,
+    subset: Subset,
+    count: usize,
+    seed: Option<u64>,
+    threads: usize,
+    solve: &Solve,
+) -> Vec<Scramble> {
+    let threads = threads.clamp(1, count.max(1));
+
+    // seeding scramble `i` with `seed + i` would mean seed 5 gives the scrambles seed 6 does, just
+    // one along, so they're all drawn from the one generator instead
+    let seeds: Option<Vec<u64>> = seed.map(|seed| {
+        let mut seeds = StdRng::seed_from_u64(seed);
+        (0..count).map(|_| seeds.gen()).collect()
+    });
+
+    let make = |i: usize| {
+        let mut rng = match &seeds {
+            Some(seeds) => StdRng::seed_from_u64(seeds[i]),
+            None => StdRng::from_rng(thread_rng()).expect("Should be able to seed from thread_rng"),
+        };
+
+        for _ in 0..MAX_ATTEMPTS {
+            let cube = scramble_subset(scheme, subset, &mut rng);
+
+            if let Some(solution) = solve(&cube) {
+                let moves = canonicalize(&invert(&solution));
+                return Scramble { cube, moves };
+            }
+        }
+
+        panic!("Couldn't find a scramble hard enough in {MAX_ATTEMPTS} tries")
+    };
+
+    // each thread takes every `threads`-th scramble, then they get put back in order
+    let mut numbered: Vec<(usize, Scramble)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let make = &make;
+                scope.spawn(move || {
+                    (t..count)
+                        .step_by(threads)
+                        .map(|i| (i, make(i)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            // if a thread gave up, so does the whole batch, with the same message
+            .flat_map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    });
+
+    numbered.sort_by_key(|(i, _)| *i);
+    numbered.into_iter().map(|(_, scramble)| scramble).collect()
+}
+
+const FACT_8: u64 = 40_320;
+const FACT_12: u64 = 479_001_600;
+
+/// The permutation of the items with the given rank, from 0 to n! - 1; that is, the rank is
+/// written in the factorial number system, and each digit picks which of the remaining items
+/// comes next. Rank 0 leaves the items alone, and the parity of the permutation is the parity of
+/// the sum of the digits; in particular the last digit which can be nonzero is the ones digit,
+/// so ranks which differ only there have opposite parity.
+fn from_rank<T: Clone>(items: &[T], mut rank: u64) -> Vec<T> {
+    let n = items.len();
+
+    // digits from the last (radix 1) up to the first (radix n)
+    let mut digits = Vec::with_capacity(n);
+    for radix in 1..=n as u64 {
+        digits.push((rank % radix) as usize);
+        rank /= radix;
+    }
+
+    assert_eq!(rank, 0, "Rank should be less than {n}!");
+
+    let mut left: Vec<T> = items.to_vec();
+    digits
+        .into_iter()
+        .rev()
+        .map(|digit| left.remove(digit))
+        .collect()
+}
+
+fn make_edge_pos(edges: &[SideCubelet]) -> CubeEdgePositions {
     assert_eq!(edges.len(), 12);
 
     CubeEdgePositions {
@@ -123,7 +637,7 @@ fn make_edge_pos(edges: &Vec<SideCubelet>) -> CubeEdgePositions {
     }
 }
 
-fn make_corner_pos(corners: &Vec<CornerCubelet>) -> CubeCornerPositions {
+fn make_corner_pos(corners: &[CornerCubelet]) -> CubeCornerPositions {
     assert_eq!(corners.len(), 8);
 
     CubeCornerPositions {
@@ -139,18 +653,18 @@ fn make_corner_pos(corners: &Vec<CornerCubelet>) -> CubeCornerPositions {
 }
 
 fn get_corner_facelets(
+    scheme: &ColorScheme,
     left_color: Facelet,
     right_color: Facelet,
     cubelet: CornerCubelet,
     orientation: CornerOrientation,
 ) -> [Facelet; 3] {
     // this is the set of facelets that is this corner, but the offset is a little dubious
-    let mut facelets = CORNERS[cubelet.to_index() as usize].clone();
+    let mut facelets = scheme.corners()[cubelet.to_index() as usize].clone();
 
     // rotate so that the first element of the facelet array is indeed the side facelet
     let good_offset = (0..3_usize)
-        .filter(|&i| facelets[i] == left_color || facelets[i] == right_color)
-        .next()
+        .find(|&i| facelets[i] == left_color || facelets[i] == right_color)
         .expect("At least one facelet on a corner should be an L/R color");
 
     facelets.rotate_left(good_offset);
@@ -171,11 +685,12 @@ fn get_corner_facelets(
 }
 
 /// Returns the edge facelets for the given position, in some order (unspecified)
-fn get_edge_facelets(cubelet: SideCubelet) -> [Facelet; 2] {
-    EDGES[cubelet.to_index() as usize].clone()
+fn get_edge_facelets(scheme: &ColorScheme, cubelet: SideCubelet) -> [Facelet; 2] {
+    scheme.edges()[cubelet.to_index() as usize].clone()
 }
 
 fn set_corner_facelets(cube: &mut Cube, pos: CubeCornerPositions, orr: CornerOrientationState) {
+    let scheme = ColorScheme::from_centers(cube);
     let l_color = cube.l.cc.clone();
     let r_color = cube.r.cc.clone();
 
@@ -184,7 +699,8 @@ fn set_corner_facelets(cube: &mut Cube, pos: CubeCornerPositions, orr: CornerOri
                         a: &mut Facelet,
                         b: &mut Facelet,
                         c: &mut Facelet| {
-        let [a_new, b_new, c_new] = get_corner_facelets(l_color.clone(), r_color.clone(), pos, orr);
+        let [a_new, b_new, c_new] =
+            get_corner_facelets(&scheme, l_color.clone(), r_color.clone(), pos, orr);
         *a = a_new;
         *b = b_new;
         *c = c_new;
@@ -252,6 +768,7 @@ fn set_corner_facelets(cube: &mut Cube, pos: CubeCornerPositions, orr: CornerOri
 }
 
 fn set_edge_facelets(cube: &mut Cube, pos: CubeEdgePositions, orr: EdgeOrientationState) {
+    let scheme = ColorScheme::from_centers(cube);
     let l_color = cube.l.cc.clone();
     let r_color = cube.r.cc.clone();
     let u_color = cube.u.cc.clone();
@@ -267,19 +784,18 @@ fn set_edge_facelets(cube: &mut Cube, pos: CubeEdgePositions, orr: EdgeOrientati
     // Look at the edges on the L/R faces. If you see:
     //      F/B color it's bad
     //      U/D color you need to look at the side of the edge. If the side is L/R it's bad.
-    let lr_good = |lr: &Facelet, other: &Facelet| {
-        (!is_fb_color(lr)) && !(is_ud_color(lr) && is_lr_color(other))
-    };
+    let lr_good =
+        |lr: &Facelet, other: &Facelet| !(is_fb_color(lr) || is_ud_color(lr) && is_lr_color(other));
 
     // Then look at the U/D edges on the mid slice. If you see:
     //      F/B color it's bad
     //      U/D color you need to look at the side of the edge. If the side is L/R it's bad.
     let ud_mid_good =
-        |ud: &Facelet, fb: &Facelet| (!is_fb_color(ud)) && !(is_ud_color(ud) && is_lr_color(fb));
+        |ud: &Facelet, fb: &Facelet| !(is_fb_color(ud) || is_ud_color(ud) && is_lr_color(fb));
 
     let set_facelets_from_side =
         |pos: SideCubelet, orr: bool, side: &mut Facelet, non_side: &mut Facelet| {
-            let [mut side_new, mut non_side_new] = get_edge_facelets(pos);
+            let [mut side_new, mut non_side_new] = get_edge_facelets(&scheme, pos);
 
             // if the orientation of the facelets doesn't match the desired orientation, flip them
 
@@ -293,7 +809,7 @@ fn set_edge_facelets(cube: &mut Cube, pos: CubeEdgePositions, orr: EdgeOrientati
 
     let set_mid_facelets_from_ud =
         |pos: SideCubelet, orr: bool, ud: &mut Facelet, non_ud: &mut Facelet| {
-            let [mut ud_new, mut non_ud_new] = get_edge_facelets(pos);
+            let [mut ud_new, mut non_ud_new] = get_edge_facelets(&scheme, pos);
 
             // if the orientation of the facelets doesn't match the desired orientation, flip them
 
@@ -324,17 +840,20 @@ fn set_edge_facelets(cube: &mut Cube, pos: CubeEdgePositions, orr: EdgeOrientati
     set_facelets_from_side(pos.br, orr.br, &mut cube.r.bc, &mut cube.b.rc);
 }
 
+/// Builds a cube from its positions and orientations, in the given color scheme; the cube is
+/// held the way the scheme describes it
 pub fn make_cube(
+    scheme: &ColorScheme,
     edge_pos: CubeEdgePositions,
     corner_pos: CubeCornerPositions,
     edge_or: EdgeOrientationState,
     corner_or: CornerOrientationState,
 ) -> Cube {
     // i really don't see a way out of how much this function sucks to write out
-    let mut cube = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+    let mut cube = Cube::make_solved_with(scheme, scheme.f.clone(), scheme.u.clone());
 
-    set_corner_facelets(&mut cube, corner_pos.clone(), corner_or.clone());
-    set_edge_facelets(&mut cube, edge_pos.clone(), edge_or.clone());
+    set_corner_facelets(&mut cube, corner_pos.clone(), corner_or);
+    set_edge_facelets(&mut cube, edge_pos.clone(), edge_or);
 
     assert_eq!(
         CubeCornerPositions::from_cube(&cube),
@@ -365,6 +884,7 @@ pub fn make_cube(
 mod tests {
     use crate::corner_orientation_state::CornerOrientationState;
     use crate::edge_orientation_state::EdgeOrientationState;
+    use crate::moves::{parse_many, to_nice_str};
 
     use super::*;
 
@@ -376,7 +896,7 @@ mod tests {
         for i in 0..MAX_ATTEMPTS {
             println!("Attempt {} of {}", i, MAX_ATTEMPTS);
 
-            let cube = scramble_any();
+            let cube = scramble_with_scheme(&ColorScheme::western());
 
             let pos = CubePositions::from_cube(&cube);
             assert!(pos.directly_solvable());
@@ -388,4 +908,386 @@ mod tests {
             assert!(corner_or.is_solvable());
         }
     }
+
+    #[test]
+    fn scramble_other_schemes() {
+        for scheme in [ColorScheme::japanese(), ColorScheme::western()] {
+            for _ in 0..20 {
+                let cube = scramble_with_scheme(&scheme);
+
+                assert_eq!(ColorScheme::from_centers(&cube), scheme);
+                assert!(CubePositions::from_cube(&cube).directly_solvable());
+                assert!(EdgeOrientationState::from_cube(&cube).is_solvable());
+                assert!(CornerOrientationState::from_cube(&cube).is_solvable());
+            }
+        }
+    }
+
+    #[test]
+    fn seeded_scrambles() {
+        let scheme = ColorScheme::western();
+        let seeded = |seed| scramble_with_rng(&scheme, &mut StdRng::seed_from_u64(seed));
+
+        assert_eq!(seeded(7), seeded(7));
+        assert_ne!(seeded(7), seeded(8));
+
+        // one generator goes on giving different cubes
+        let mut rng = StdRng::seed_from_u64(7);
+        let first = scramble_with_rng(&scheme, &mut rng);
+        assert_eq!(first, seeded(7));
+        assert_ne!(scramble_with_rng(&scheme, &mut rng), first);
+    }
+
+    #[test]
+    fn ranks() {
+        let items = [0, 1, 2, 3];
+
+        assert_eq!(from_rank(&items, 0), vec![0, 1, 2, 3]);
+        assert_eq!(from_rank(&items, 1), vec![0, 1, 3, 2]);
+        assert_eq!(from_rank(&items, 23), vec![3, 2, 1, 0]);
+
+        // every rank gives a different permutation
+        let all: std::collections::HashSet<Vec<i32>> =
+            (0..24).map(|rank| from_rank(&items, rank)).collect();
+        assert_eq!(all.len(), 24);
+    }
+
+    /// Counts how often each corner twist and each edge flip turns up in every slot, and checks
+    /// them against uniform with a chi-squared test. The seed is fixed so this can't flake;
+    /// the cutoffs are for p = 0.001.
+    #[test]
+    fn uniform_marginals() {
+        const SAMPLES: usize = 3000;
+
+        let mut rng = StdRng::seed_from_u64(2024);
+        let scheme = ColorScheme::western();
+
+        // eight corners with three twists each, then twelve edges flipped or not
+        let mut twists = [[0_usize; 3]; 8];
+        let mut flips = [[0_usize; 2]; 12];
+
+        for _ in 0..SAMPLES {
+            let cube = scramble_with_rng(&scheme, &mut rng);
+
+            let c = CornerOrientationState::from_cube(&cube);
+            let corners = [c.ful, c.fur, c.fdl, c.fdr, c.bul, c.bur, c.bdl, c.bdr];
+            for (counts, twist) in twists.iter_mut().zip(corners) {
+                let i = match twist {
+                    CornerOrientation::Good => 0,
+                    CornerOrientation::CW => 1,
+                    CornerOrientation::CCW => 2,
+                };
+                counts[i] += 1;
+            }
+
+            let e = EdgeOrientationState::from_cube(&cube);
+            for (i, counts) in flips.iter_mut().enumerate() {
+                counts[e.ind(i as u8) as usize] += 1;
+            }
+        }
+
+        fn chi_squared(counts: &[usize]) -> f64 {
+            let expected = SAMPLES as f64 / counts.len() as f64;
+            counts
+                .iter()
+                .map(|&c| (c as f64 - expected).powi(2) / expected)
+                .sum()
+        }
+
+        for counts in twists {
+            assert!(chi_squared(&counts) < 13.82, "Corner twists {counts:?}");
+        }
+
+        for counts in flips {
+            assert!(chi_squared(&counts) < 10.83, "Edge flips {counts:?}");
+        }
+    }
+
+    #[test]
+    fn batches() {
+        let scheme = ColorScheme::western();
+        // the moves don't matter here, so skip the expensive solver
+        let solve = |_: &Cube| Some(Vec::new());
+
+        let one = scramble_batch(&scheme, Subset::Full, 10, Some(5), 1, &solve);
+        let many = scramble_batch(&scheme, Subset::Full, 10, Some(5), 4, &solve);
+
+        assert_eq!(one.len(), 10);
+        assert_eq!(one, many);
+
+        let mut seeds = StdRng::seed_from_u64(5);
+        for scramble in &one {
+            let seed = seeds.gen();
+            let expected = scramble_with_rng(&scheme, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(scramble.cube, expected);
+        }
+
+        // nearby seeds don't give the same scrambles shifted along
+        let next = scramble_batch(&scheme, Subset::Full, 10, Some(6), 1, &solve);
+        assert!(next.iter().all(|scramble| !one.contains(scramble)));
+
+        // and more threads than scrambles is fine too
+        assert_eq!(
+            scramble_batch(&scheme, Subset::Full, 2, None, 8, &solve).len(),
+            2
+        );
+        assert!(scramble_batch(&scheme, Subset::Full, 0, None, 8, &solve).is_empty());
+    }
+
+    #[test]
+    fn canonical_scrambles() {
+        let scheme = ColorScheme::western();
+        let solved = Cube::make_solved_with(&scheme, scheme.f.clone(), scheme.u.clone());
+
+        // "solves" which waste moves and put opposite faces the wrong way round (for a scramble)
+        let solutions = ["R U U' F B2 D", "U2 R2 R' L F' F' B", "L D D U U'", "R R'"];
+
+        for solution in solutions {
+            let moves = parse_many(solution);
+            let scramble = Scramble {
+                cube: solved.clone().apply_many(&invert(&moves)),
+                moves: canonicalize(&invert(&moves)),
+            };
+            assert!(scramble.is_valid(&scheme), "{solution}");
+            assert!(scramble
+                .moves
+                .windows(2)
+                .all(|w| crate::dfs_util::can_follow(Some(w[0].dir), w[1].dir)));
+            assert!(scramble.moves.len() < moves.len());
+        }
+
+        let bad = Scramble {
+            cube: solved.clone(),
+            moves: parse_many("R"),
+        };
+        assert!(!bad.is_valid(&scheme));
+
+        // and the batches clean their moves up the same way
+        let solve = |_: &Cube| Some(parse_many("F F U D"));
+        let batch = scramble_batch(&scheme, Subset::Full, 1, Some(46), 1, &solve);
+        assert_eq!(to_nice_str(&batch[0].moves), "D' U' F2");
+    }
+
+    #[test]
+    fn random_move_scrambles() {
+        let mut rng = StdRng::seed_from_u64(47);
+        let all = [Dir::U, Dir::D, Dir::F, Dir::B, Dir::L, Dir::R];
+
+        for dirs in [&all[..], &[Dir::R, Dir::U], &[Dir::U, Dir::D, Dir::R]] {
+            for length in [0, 1, 5, 25, 100] {
+                let moves = random_moves(dirs, length, &mut rng);
+
+                assert_eq!(moves.len(), length);
+                assert!(moves.iter().all(|fm| dirs.contains(&fm.dir)));
+                assert!(moves
+                    .windows(2)
+                    .all(|w| can_follow(Some(w[0].dir), w[1].dir)));
+                assert_eq!(canonicalize(&moves), moves);
+            }
+        }
+
+        let again = |seed| random_moves(&all, 25, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(again(1), again(1));
+        assert_ne!(again(1), again(2));
+
+        // shallow positions are handy for checking solvers; the cross can't take longer than the
+        // scramble did
+        let cache = CrossCache::initialize();
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+        for length in 0..=4 {
+            let moves = random_moves(&all, length, &mut rng);
+            let cube = solved.clone().apply_many(&moves);
+            let cross = crate::solve::solve_cross(&cube, &Facelet::White, &cache);
+            assert!(cross.moves.len() <= length, "{}", to_nice_str(&moves));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't make 3 moves")]
+    fn random_moves_can_run_out() {
+        random_moves(&[Dir::U, Dir::D], 3, &mut StdRng::seed_from_u64(47));
+    }
+
+    #[test]
+    fn rejects_and_replaces() {
+        let scheme = ColorScheme::western();
+
+        // throw out most of the cubes, by something which has nothing to do with the seed
+        let picky = |cube: &Cube| (cube.u.fc == cube.u.cc).then(Vec::new);
+
+        let one = scramble_batch(&scheme, Subset::Full, 10, Some(45), 1, &picky);
+        let many = scramble_batch(&scheme, Subset::Full, 10, Some(45), 3, &picky);
+        assert_eq!(one, many);
+
+        for scramble in &one {
+            assert!(picky(&scramble.cube).is_some());
+        }
+
+        // so some of them had to be made again
+        let mut seeds = StdRng::seed_from_u64(45);
+        let first_tries = one
+            .iter()
+            .filter(|scramble| {
+                let first = scramble_with_rng(&scheme, &mut StdRng::seed_from_u64(seeds.gen()));
+                scramble.cube == first
+            })
+            .count();
+        assert!(first_tries < 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "hard enough")]
+    fn gives_up_eventually() {
+        scramble_batch(
+            &ColorScheme::western(),
+            Subset::Full,
+            1,
+            Some(45),
+            1,
+            &|_: &Cube| None,
+        );
+    }
+
+    #[test]
+    fn difficulty() {
+        let cache = CrossCache::initialize();
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+        let cube = solved.clone().apply_many(&parse_many("R U F"));
+
+        let phase1 = |_: &Cube| parse_many("F'");
+        let phase2 = |_: &Cube| parse_many("U' R'");
+
+        let easy = Difficulty::default();
+        assert_eq!(
+            easy.solve(&cube, &cache, phase1, phase2),
+            Some(parse_many("F' U' R'"))
+        );
+
+        let cross = best_cross(&solve_all_crosses(&cube, &cache)).moves.len();
+        assert!(cross > 0);
+
+        let limits = [
+            (cross, 1, 3, 3, true),
+            (0, 2, 0, 0, false),
+            (0, 0, 4, 0, false),
+            (cross + 1, 0, 0, 0, false),
+            (0, 0, 0, 2, false),
+        ];
+        for (min_cross, min_phase1, min_solution, max_solution, ok) in limits {
+            let difficulty = Difficulty {
+                min_cross,
+                min_phase1,
+                min_solution,
+                max_solution,
+            };
+            let sol = difficulty.solve(&cube, &cache, phase1, phase2);
+            assert_eq!(sol.is_some(), ok, "{difficulty:?}");
+        }
+
+        // where the phases meet, they get tidied up before counting
+        let overlap = |_: &Cube| parse_many("R");
+        let sol = easy.solve(&cube, &cache, phase1, overlap);
+        assert_eq!(sol, Some(parse_many("F' R")));
+        let sol = easy.solve(&cube, &cache, overlap, overlap);
+        assert_eq!(sol, Some(parse_many("R2")));
+
+        // a solved cross means the phases never even get looked at
+        let never = |_: &Cube| -> Vec<FullMove> { panic!("Shouldn't get this far") };
+        let difficulty = Difficulty {
+            min_cross: 1,
+            ..Difficulty::default()
+        };
+        assert_eq!(difficulty.solve(&solved, &cache, never, never), None);
+    }
+
+    #[test]
+    fn subsets() {
+        use crate::corner_position_state::CubeCornerPositions;
+        use crate::edge_slice_state::EdgeMidSliceState;
+        use crate::shadow::MaskSpec;
+
+        let scheme = ColorScheme::western();
+        let solved = Cube::make_solved_with(&scheme, scheme.f.clone(), scheme.u.clone());
+        let mut rng = StdRng::seed_from_u64(44);
+
+        // what each one has to leave alone
+        let kept = |subset| match subset {
+            Subset::Full | Subset::Domino => None,
+            Subset::LastLayer => Some("solved: D* FL FR BL BR"),
+            Subset::F2l => Some("solved: DF DR DB DL"),
+            Subset::TwoGen => Some("solved: DF DB DL FL BL DFL DBL"),
+            Subset::ThreeGen => Some("solved: DB DL BL DBL"),
+            Subset::EdgesOnly => Some("solved: UFL UFR UBL UBR DFL DFR DBL DBR"),
+            Subset::CornersOnly => Some("solved: UF UR UB UL FL FR BL BR DF DR DB DL"),
+        };
+
+        for subset in ALL_SUBSETS {
+            assert_eq!(Subset::try_from(subset.to_string().as_str()), Ok(subset));
+
+            let mut all_solved = true;
+
+            for _ in 0..20 {
+                let cube = scramble_subset(&scheme, subset, &mut rng);
+
+                assert!(CubePositions::from_cube(&cube).directly_solvable());
+                assert!(EdgeOrientationState::from_cube(&cube).is_solvable());
+                assert!(CornerOrientationState::from_cube(&cube).is_solvable());
+
+                if let Some(spec) = kept(subset) {
+                    let spec = MaskSpec::try_from(spec).unwrap();
+                    assert!(spec.apply(cube.clone()).is_solved(), "{subset}");
+                }
+
+                if subset.keeps_cross() {
+                    let cross = MaskSpec::try_from("solved: DF DR DB DL").unwrap();
+                    assert!(cross.apply(cube.clone()).is_solved(), "{subset}");
+                }
+
+                if subset == Subset::Domino {
+                    assert!(EdgeOrientationState::from_cube(&cube).is_solved());
+                    assert!(CornerOrientationState::from_cube(&cube).is_solved());
+                    assert!(EdgeMidSliceState::from_cube(&cube).is_solved());
+                }
+
+                if subset == Subset::EdgesOnly {
+                    assert_eq!(
+                        CubeCornerPositions::from_cube(&cube),
+                        CubeCornerPositions::from_cube(&solved)
+                    );
+                }
+
+                all_solved &= cube == solved;
+            }
+
+            assert!(!all_solved, "{subset} should actually scramble something");
+        }
+    }
+
+    #[test]
+    fn generator_scrambles() {
+        use crate::generators::{solve_three_gen, solve_two_gen, GeneratorCaches};
+
+        let scheme = ColorScheme::western();
+        let caches = GeneratorCaches::initialize();
+
+        for subset in [Subset::TwoGen, Subset::ThreeGen] {
+            let solve = |cube: &Cube| match subset {
+                Subset::TwoGen => Some(solve_two_gen(cube, &caches)),
+                _ => Some(solve_three_gen(cube, &caches)),
+            };
+            let generators = subset.generators().unwrap();
+
+            for scramble in scramble_batch(&scheme, subset, 5, Some(3), 2, &solve) {
+                assert!(scramble.is_valid(&scheme));
+                assert!(
+                    scramble.moves.iter().all(|fm| generators.contains(&fm.dir)),
+                    "{subset}: {}",
+                    to_nice_str(&scramble.moves)
+                );
+            }
+        }
+
+        assert_eq!(Subset::Full.generators(), None);
+        assert_eq!(Subset::Domino.generators(), None);
+    }
 }
@@ -0,0 +1,461 @@
+//! Module for practice sessions: the time, penalty and scramble of every solve, with the usual
+//! statistics on top (mean of 3, averages of 5, 12 and 100, and best single).
+//!
+//! Sessions are saved as plain text, one solve per line: when it happened (in seconds since the
+//! Unix epoch), the time in milliseconds, the penalty (OK, +2 or DNF), and the scramble, all
+//! separated by tabs. Lines starting with # are ignored. They can also be exported as CSV, which
+//! is friendlier to spreadsheets.
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash)]
+pub enum Penalty {
+    #[default]
+    Clean,
+    /// Two seconds added, e.g. for a last layer one turn off
+    PlusTwo,
+    /// Did not finish; this solve counts as worse than any time
+    Dnf,
+}
+
+impl<'a> TryFrom<&'a str> for Penalty {
+    type Error = &'a str;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "ok" => Ok(Penalty::Clean),
+            "+2" | "2" => Ok(Penalty::PlusTwo),
+            "dnf" | "d" => Ok(Penalty::Dnf),
+            _ => Err(value),
+        }
+    }
+}
+
+impl Display for Penalty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Penalty::Clean => write!(f, "OK"),
+            Penalty::PlusTwo => write!(f, "+2"),
+            Penalty::Dnf => write!(f, "DNF"),
+        }
+    }
+}
+
+/// What a solve (or an average of them) comes to once the penalties are in. A DNF is worse than
+/// any time, so the best of some outcomes is just the smallest.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Hash)]
+pub enum Outcome {
+    Time(Duration),
+    Dnf,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Time(time) => write!(f, "{}", format_time(*time)),
+            Outcome::Dnf => write!(f, "DNF"),
+        }
+    }
+}
+
+/// A time the way a timer shows it, to the hundredth (rounded down, like a stackmat), with
+/// minutes if there are any: "9.87" or "1:02.34"
+pub fn format_time(time: Duration) -> String {
+    let hundredths = time.as_millis() / 10;
+    let (minutes, hundredths) = (hundredths / 6000, hundredths % 6000);
+
+    if minutes > 0 {
+        format!("{minutes}:{:02}.{:02}", hundredths / 100, hundredths % 100)
+    } else {
+        format!("{}.{:02}", hundredths / 100, hundredths % 100)
+    }
+}
+
+/// Reads a time like "9.87", "12" or "1:02.345"; the opposite of [format_time]
+pub fn parse_time(text: &str) -> Result<Duration, String> {
+    let bad = || format!("Bad time: {text} (expected something like 12.34 or 1:02.34)");
+
+    // with minutes there, the seconds have to be under a minute, even if it's "0:"
+    let (minutes, seconds, has_minutes) = match text.trim().split_once(':') {
+        Some((minutes, seconds)) => (minutes.parse::<u64>().map_err(|_| bad())?, seconds, true),
+        None => (0, text.trim(), false),
+    };
+
+    let seconds: f64 = seconds.parse().map_err(|_| bad())?;
+    // is_sign_negative catches "-0" too, which isn't less than zero
+    if !seconds.is_finite() || seconds.is_sign_negative() || (has_minutes && seconds >= 60.0) {
+        return Err(bad());
+    }
+
+    let millis = minutes * 60_000 + (seconds * 1000.0).round() as u64;
+    Ok(Duration::from_millis(millis))
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Solve {
+    /// How long it took, not counting the penalty
+    pub time: Duration,
+    pub penalty: Penalty,
+    pub scramble: String,
+    /// When it happened, in seconds since the Unix epoch
+    pub date: u64,
+}
+
+impl Solve {
+    pub fn outcome(&self) -> Outcome {
+        match self.penalty {
+            Penalty::Clean => Outcome::Time(self.time),
+            Penalty::PlusTwo => Outcome::Time(self.time + Duration::from_secs(2)),
+            Penalty::Dnf => Outcome::Dnf,
+        }
+    }
+}
+
+/// Means and averages are rounded to the nearest hundredth, the way the WCA does it; only single
+/// times get cut off (by [format_time])
+fn round_hundredths(time: Duration) -> Duration {
+    let hundredths = (time.as_micros() + 5_000) / 10_000;
+    Duration::from_millis(hundredths as u64 * 10)
+}
+
+/// Plain mean of the solves; any DNF makes the whole thing a DNF
+fn mean(solves: &[Solve]) -> Outcome {
+    let mut total = Duration::ZERO;
+
+    for solve in solves {
+        match solve.outcome() {
+            Outcome::Time(time) => total += time,
+            Outcome::Dnf => return Outcome::Dnf,
+        }
+    }
+
+    Outcome::Time(round_hundredths(total / solves.len() as u32))
+}
+
+/// WCA-style average: the best and worst 5% (rounded up, so one each for an ao5 or ao12) are
+/// dropped, and the rest are averaged. DNFs count as the worst, so one can be dropped, but any
+/// more than that make the average a DNF. None if that would drop every solve, which is anything
+/// under three.
+fn average(solves: &[Solve]) -> Option<Outcome> {
+    let trim = solves.len().div_ceil(20);
+    if solves.len() <= 2 * trim {
+        return None;
+    }
+
+    let mut outcomes: Vec<Outcome> = solves.iter().map(Solve::outcome).collect();
+    outcomes.sort();
+
+    let kept = &outcomes[trim..outcomes.len() - trim];
+    let mut total = Duration::ZERO;
+
+    for outcome in kept {
+        match outcome {
+            Outcome::Time(time) => total += *time,
+            Outcome::Dnf => return Some(Outcome::Dnf),
+        }
+    }
+
+    Some(Outcome::Time(round_hundredths(total / kept.len() as u32)))
+}
+
+/// The numbers everyone wants to see; each one is None if there aren't enough solves for it yet
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Stats {
+    pub count: usize,
+    pub best: Option<Outcome>,
+    pub mo3: Option<Outcome>,
+    pub ao5: Option<Outcome>,
+    pub ao12: Option<Outcome>,
+    pub ao100: Option<Outcome>,
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let show = |stat: Option<Outcome>| stat.map_or("-".to_string(), |o| o.to_string());
+
+        write!(
+            f,
+            "solves: {}  best: {}  mo3: {}  ao5: {}  ao12: {}  ao100: {}",
+            self.count,
+            show(self.best),
+            show(self.mo3),
+            show(self.ao5),
+            show(self.ao12),
+            show(self.ao100)
+        )
+    }
+}
+
+/// Every solve from a practice session, oldest first
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Session {
+    solves: Vec<Solve>,
+}
+
+impl Session {
+    pub fn push(&mut self, solve: Solve) {
+        self.solves.push(solve);
+    }
+
+    pub fn solves(&self) -> &[Solve] {
+        &self.solves
+    }
+
+    pub fn best_single(&self) -> Option<Outcome> {
+        self.solves.iter().map(Solve::outcome).min()
+    }
+
+    /// Mean of the last `n` solves
+    pub fn mean_of(&self, n: usize) -> Option<Outcome> {
+        self.last(n).map(mean)
+    }
+
+    /// Average of the last `n` solves, dropping the best and worst; None for fewer than three,
+    /// since then there's nothing left
+    pub fn average_of(&self, n: usize) -> Option<Outcome> {
+        self.last(n).and_then(average)
+    }
+
+    fn last(&self, n: usize) -> Option<&[Solve]> {
+        let len = self.solves.len();
+        (n > 0 && n <= len).then(|| &self.solves[len - n..])
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            count: self.solves.len(),
+            best: self.best_single(),
+            mo3: self.mean_of(3),
+            ao5: self.average_of(5),
+            ao12: self.average_of(12),
+            ao100: self.average_of(100),
+        }
+    }
+
+    /// Reads a session file; a file which isn't there yet is just an empty session
+    pub fn load(path: &str) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Session::try_from(text.as_str()).map_err(|e| format!("{path}: {e}")),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Session::default()),
+            Err(e) => Err(format!("{path}: {e}")),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_string()).map_err(|e| format!("{path}: {e}"))
+    }
+
+    /// Every solve as a row, along with the ao5 and ao12 as of that solve
+    pub fn to_csv(&self) -> String {
+        let mut out = "Number,Time,Penalty,Result,ao5,ao12,Scramble,Date\n".to_string();
+
+        for (i, solve) in self.solves.iter().enumerate() {
+            let so_far = &self.solves[..=i];
+            let avg = |n: usize| {
+                let start = so_far.len().checked_sub(n);
+                let average = start.and_then(|start| average(&so_far[start..]));
+                average.map_or(String::new(), |average| average.to_string())
+            };
+
+            out.push_str(&format!(
+                "{},{},{},{},{},{},\"{}\",{}\n",
+                i + 1,
+                format_time(solve.time),
+                solve.penalty,
+                solve.outcome(),
+                avg(5),
+                avg(12),
+                solve.scramble.replace('"', "\"\""),
+                solve.date
+            ));
+        }
+
+        out
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Session {
+    type Error = String;
+
+    fn try_from(text: &'a str) -> Result<Self, Self::Error> {
+        let mut solves = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fail = |what: &str| format!("Line {}: {what}", i + 1);
+
+            let mut fields = line.splitn(4, '\t');
+            let mut next = |name: &str| {
+                fields
+                    .next()
+                    .ok_or_else(|| fail(&format!("missing the {name}")))
+            };
+
+            let date = next("date")?
+                .parse()
+                .map_err(|_| fail("date should be a number of seconds"))?;
+            let time = next("time")?
+                .parse()
+                .map_err(|_| fail("time should be a number of milliseconds"))?;
+            let penalty = next("penalty")?;
+            let penalty =
+                Penalty::try_from(penalty).map_err(|p| fail(&format!("unknown penalty {p}")))?;
+            let scramble = next("scramble")?.to_string();
+
+            solves.push(Solve {
+                time: Duration::from_millis(time),
+                penalty,
+                scramble,
+                date,
+            });
+        }
+
+        Ok(Session { solves })
+    }
+}
+
+impl Display for Session {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# date\ttime (ms)\tpenalty\tscramble")?;
+
+        for solve in &self.solves {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}",
+                solve.date,
+                solve.time.as_millis(),
+                solve.penalty,
+                solve.scramble
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(times: &[&str]) -> Session {
+        let mut out = Session::default();
+
+        for (i, time) in times.iter().enumerate() {
+            let (time, penalty) = match time.split_once(' ') {
+                Some((time, penalty)) => (time, Penalty::try_from(penalty).unwrap()),
+                None => (*time, Penalty::Clean),
+            };
+
+            out.push(Solve {
+                time: parse_time(time).unwrap(),
+                penalty,
+                scramble: format!("R U F{i}"),
+                date: 1_700_000_000 + i as u64,
+            });
+        }
+
+        out
+    }
+
+    fn time(text: &str) -> Option<Outcome> {
+        Some(Outcome::Time(parse_time(text).unwrap()))
+    }
+
+    #[test]
+    fn times() {
+        for (text, shown) in [
+            ("9.87", "9.87"),
+            ("12", "12.00"),
+            ("1:02.34", "1:02.34"),
+            ("59.999", "59.99"),
+            ("0.5", "0.50"),
+            ("75.5", "1:15.50"),
+            ("0:59.5", "59.50"),
+        ] {
+            assert_eq!(format_time(parse_time(text).unwrap()), shown, "{text}");
+        }
+
+        for bad in ["", "abc", "-1", "-0", "1:75", "0:75", "0:60", "1:", "inf"] {
+            assert!(parse_time(bad).is_err(), "{bad}");
+        }
+
+        assert_eq!(Penalty::try_from("+2"), Ok(Penalty::PlusTwo));
+        assert_eq!(Penalty::try_from("dnf"), Ok(Penalty::Dnf));
+        assert_eq!(Penalty::try_from(""), Ok(Penalty::Clean));
+        assert!(Penalty::try_from("+3").is_err());
+    }
+
+    #[test]
+    fn averages() {
+        let s = session(&["10", "12", "11", "14", "9"]);
+        assert_eq!(s.best_single(), time("9"));
+        assert_eq!(s.mean_of(3), time("11.33"));
+        // drop the 9 and the 14
+        assert_eq!(s.average_of(5), time("11"));
+        assert_eq!(s.average_of(12), None);
+        assert_eq!(s.average_of(3), time("11"));
+
+        // with one or two solves, dropping the best and worst leaves nothing
+        assert_eq!(s.average_of(2), None);
+        assert_eq!(s.average_of(1), None);
+        assert_eq!(s.average_of(0), None);
+
+        // a +2 counts, and one DNF is dropped as the worst
+        let s = session(&["10", "12 +2", "11", "14 DNF", "9"]);
+        assert_eq!(s.average_of(5), time("11.67"));
+        assert_eq!(s.mean_of(3), Some(Outcome::Dnf));
+        assert_eq!(s.best_single(), time("9"));
+
+        // but two DNFs is too many
+        let s = session(&["10 DNF", "12", "11", "14 DNF", "9"]);
+        assert_eq!(s.average_of(5), Some(Outcome::Dnf));
+
+        // means and averages round to the nearest hundredth, but singles get cut off
+        let s = session(&["10.005", "10.005", "10.005"]);
+        assert_eq!(s.mean_of(3), time("10.01"));
+        assert_eq!(s.average_of(3), time("10.01"));
+        assert_eq!(s.best_single().unwrap().to_string(), "10.00");
+
+        // only the most recent ones count
+        let s = session(&["1", "1", "1", "20", "20", "20"]);
+        assert_eq!(s.mean_of(3), time("20"));
+        assert_eq!(s.best_single(), time("1"));
+
+        // an ao100 drops five from each end; here that's the two DNFs and 98 to 100 from the
+        // top, and 3 to 7 from the bottom
+        let mut times: Vec<String> = (1..=100).map(|i| i.to_string()).collect();
+        times[0] = "1 DNF".to_string();
+        times[1] = "2 DNF".to_string();
+        let times: Vec<&str> = times.iter().map(|t| t.as_str()).collect();
+        let s = session(&times);
+        let middle: u64 = (8..=97).map(|i| i * 1000).sum::<u64>() / 90;
+        assert_eq!(
+            s.average_of(100),
+            Some(Outcome::Time(Duration::from_millis(middle)))
+        );
+        assert_eq!(s.stats().ao100, s.average_of(100));
+        assert_eq!(s.stats().count, 100);
+    }
+
+    #[test]
+    fn files_and_csv() {
+        let s = session(&["10", "12 +2", "11", "14 DNF", "9"]);
+
+        let text = s.to_string();
+        assert_eq!(Session::try_from(text.as_str()), Ok(s.clone()));
+
+        assert!(Session::try_from("123\t456\tmaybe\tR U").is_err());
+        assert!(Session::try_from("123\t456").is_err());
+        assert_eq!(Session::try_from("# nothing\n\n"), Ok(Session::default()));
+
+        let csv = s.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[2], "2,12.00,+2,14.00,,,\"R U F1\",1700000001");
+        assert_eq!(lines[5], "5,9.00,OK,9.00,11.67,,\"R U F4\",1700000004");
+    }
+}
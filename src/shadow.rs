@@ -1,11 +1,14 @@
 //! Module for "shadowing" a cube. That is, blocking out "irrelevant" pieces for the purpose
 //! of solving to a particular point, such as white cross, F2L, OLL, and so on.
 
+use std::fmt::{Display, Formatter};
+
 use crate::cube::{AmbigFacelet, Cube, FBFace, Facelet, LRFace, UDFace};
+use crate::moves::Dir;
 
-impl Into<AmbigFacelet> for Facelet {
-    fn into(self) -> AmbigFacelet {
-        match self {
+impl From<Facelet> for AmbigFacelet {
+    fn from(f: Facelet) -> AmbigFacelet {
+        match f {
             Facelet::Yellow => AmbigFacelet::Yellow,
             Facelet::White => AmbigFacelet::White,
             Facelet::Green => AmbigFacelet::Green,
@@ -16,6 +19,12 @@ impl Into<AmbigFacelet> for Facelet {
     }
 }
 
+impl From<Cube> for Cube<AmbigFacelet> {
+    fn from(cube: Cube) -> Cube<AmbigFacelet> {
+        cube.apply_mask(CubeMask::from_val(true))
+    }
+}
+
 fn apply_mask(f: Facelet, mask: bool) -> AmbigFacelet {
     if mask {
         f.into()
@@ -164,14 +173,71 @@ impl CubeMask {
         self.b.cc = true;
     }
 
-    fn keep_white_edges(&mut self, cube: &Cube) {
+    fn keep_edges_with(&mut self, cube: &Cube, color: &Facelet) {
         for (edge_a, edge_b, mask_a, mask_b) in edges_mut(cube, self) {
-            if edge_a == &Facelet::White || edge_b == &Facelet::White {
+            if edge_a == color || edge_b == color {
                 *mask_a = true;
                 *mask_b = true;
             }
         }
     }
+
+    fn keep_color(&mut self, cube: &Cube, color: &Facelet) {
+        let kept = Cube::from_fn(|face, row, col| cube.face_grid(face)[row][col] == color);
+
+        *self = Cube::from_fn(|face, row, col| {
+            *self.face_grid(face)[row][col] || *kept.face_grid(face)[row][col]
+        });
+    }
+
+    /// Keeps the edge with exactly these two colors, wherever it is
+    fn keep_edge(&mut self, cube: &Cube, colors: [&Facelet; 2]) {
+        for (edge_a, edge_b, mask_a, mask_b) in edges_mut(cube, self) {
+            if colors.contains(&edge_a) && colors.contains(&edge_b) {
+                *mask_a = true;
+                *mask_b = true;
+            }
+        }
+    }
+
+    /// Keeps the corner with exactly these three colors, wherever it is
+    fn keep_corner(&mut self, cube: &Cube, colors: [&Facelet; 3]) {
+        for (corner, mask) in corners_mut(cube, self) {
+            if corner.iter().all(|c| colors.contains(c)) {
+                for m in mask {
+                    *m = true;
+                }
+            }
+        }
+    }
+}
+
+impl CubeMask {
+    /// Keeps just the facelet of the given color on the edge with exactly these two colors
+    fn keep_edge_facelet(&mut self, cube: &Cube, colors: [&Facelet; 2], kept: &Facelet) {
+        for (edge_a, edge_b, mask_a, mask_b) in edges_mut(cube, self) {
+            if colors.contains(&edge_a) && colors.contains(&edge_b) {
+                if edge_a == kept {
+                    *mask_a = true;
+                } else {
+                    *mask_b = true;
+                }
+            }
+        }
+    }
+
+    /// Keeps just the facelet of the given color on the corner with exactly these three colors
+    fn keep_corner_facelet(&mut self, cube: &Cube, colors: [&Facelet; 3], kept: &Facelet) {
+        for (corner, mask) in corners_mut(cube, self) {
+            if corner.iter().all(|c| colors.contains(c)) {
+                for (c, m) in corner.into_iter().zip(mask) {
+                    if c == kept {
+                        *m = true;
+                    }
+                }
+            }
+        }
+    }
 }
 
 fn edges_mut<'a>(
@@ -197,34 +263,499 @@ fn edges_mut<'a>(
     ]
 }
 
+type CornerFacelets<'a> = ([&'a Facelet; 3], [&'a mut bool; 3]);
+
+fn corners_mut<'a>(cube: &'a Cube, mask: &'a mut CubeMask) -> [CornerFacelets<'a>; 8] {
+    let Cube { u, d, f, b, l, r } = mask;
+
+    [
+        // top four corners ...
+        (
+            [&cube.u.fl, &cube.f.ul, &cube.l.uf],
+            [&mut u.fl, &mut f.ul, &mut l.uf],
+        ),
+        (
+            [&cube.u.fr, &cube.f.ur, &cube.r.uf],
+            [&mut u.fr, &mut f.ur, &mut r.uf],
+        ),
+        (
+            [&cube.u.bl, &cube.b.ul, &cube.l.ub],
+            [&mut u.bl, &mut b.ul, &mut l.ub],
+        ),
+        (
+            [&cube.u.br, &cube.b.ur, &cube.r.ub],
+            [&mut u.br, &mut b.ur, &mut r.ub],
+        ),
+        // ... bottom four corners
+        (
+            [&cube.d.fl, &cube.f.dl, &cube.l.df],
+            [&mut d.fl, &mut f.dl, &mut l.df],
+        ),
+        (
+            [&cube.d.fr, &cube.f.dr, &cube.r.df],
+            [&mut d.fr, &mut f.dr, &mut r.df],
+        ),
+        (
+            [&cube.d.bl, &cube.b.dl, &cube.l.db],
+            [&mut d.bl, &mut b.dl, &mut l.db],
+        ),
+        (
+            [&cube.d.br, &cube.b.dr, &cube.r.db],
+            [&mut d.br, &mut b.dr, &mut r.db],
+        ),
+    ]
+}
+
+/// One of the four F2L slots; that is, a bottom corner along with the middle edge above it
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Slot {
+    FL,
+    FR,
+    BL,
+    BR,
+}
+
+pub const ALL_SLOTS: [Slot; 4] = [Slot::FL, Slot::FR, Slot::BL, Slot::BR];
+
+impl Slot {
+    /// Center colors of the two side faces this slot sits between, for the cube as it's held
+    fn side_colors(self, cube: &Cube) -> [&Facelet; 2] {
+        match self {
+            Slot::FL => [&cube.f.cc, &cube.l.cc],
+            Slot::FR => [&cube.f.cc, &cube.r.cc],
+            Slot::BL => [&cube.b.cc, &cube.l.cc],
+            Slot::BR => [&cube.b.cc, &cube.r.cc],
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Slot {
+    type Error = &'a str;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value {
+            "FL" => Ok(Slot::FL),
+            "FR" => Ok(Slot::FR),
+            "BL" => Ok(Slot::BL),
+            "BR" => Ok(Slot::BR),
+            other => Err(other),
+        }
+    }
+}
+
 pub fn to_white_cross(cube: Cube) -> Cube<AmbigFacelet> {
+    to_cross(cube, &Facelet::White)
+}
+
+/// Same as [to_white_cross], but for the cross of any color
+pub fn to_cross(cube: Cube, color: &Facelet) -> Cube<AmbigFacelet> {
+    let mut mask = CubeMask::from_val(false);
+
+    mask.keep_centers();
+    mask.keep_edges_with(&cube, color);
+
+    cube.apply_mask(mask)
+}
+
+/// Keeps the centers and the DF and DB edges; this is the line from ZZ's EOLine
+pub fn to_line(cube: Cube) -> Cube<AmbigFacelet> {
+    let mut mask = CubeMask::from_val(false);
+
+    mask.keep_centers();
+    mask.keep_edge(&cube, [&cube.d.cc, &cube.f.cc]);
+    mask.keep_edge(&cube, [&cube.d.cc, &cube.b.cc]);
+
+    cube.apply_mask(mask)
+}
+
+/// Keeps only the centers and the corner and edge which belong in the given slot, assuming the
+/// cross is on D
+pub fn to_pair(cube: Cube, slot: Slot) -> Cube<AmbigFacelet> {
+    let mut mask = CubeMask::from_val(false);
+
+    mask.keep_centers();
+
+    let [a, b] = slot.side_colors(&cube);
+    mask.keep_edge(&cube, [a, b]);
+    mask.keep_corner(&cube, [a, b, &cube.d.cc]);
+
+    cube.apply_mask(mask)
+}
+
+/// Keeps the cross on D, plus the pairs for the given slots; solving this is an xcross (for
+/// one slot), an xxcross (for two), and so on
+pub fn to_cross_and_pairs(cube: Cube, slots: &[Slot]) -> Cube<AmbigFacelet> {
+    let mask = cross_and_pairs_mask(&cube, slots);
+
+    cube.apply_mask(mask)
+}
+
+fn cross_and_pairs_mask(cube: &Cube, slots: &[Slot]) -> CubeMask {
+    let mut mask = CubeMask::from_val(false);
+
+    mask.keep_centers();
+    mask.keep_edges_with(cube, &cube.d.cc);
+
+    for &slot in slots {
+        let [a, b] = slot.side_colors(cube);
+        mask.keep_edge(cube, [a, b]);
+        mask.keep_corner(cube, [a, b, &cube.d.cc]);
+    }
+
+    mask
+}
+
+/// Keeps the first two layers (with the cross on D), and only the U color of the last layer;
+/// solving this is OLL
+pub fn to_oll(cube: Cube) -> Cube<AmbigFacelet> {
+    let mut mask = cross_and_pairs_mask(&cube, &ALL_SLOTS);
+
+    mask.keep_color(&cube, &cube.u.cc);
+
+    cube.apply_mask(mask)
+}
+
+/// Keeps one 1x2x2 square of a Roux block: the pair for the slot, and the D edge below it on
+/// the same side. Two of these on the same side make a block.
+pub fn to_block_square(cube: Cube, slot: Slot) -> Cube<AmbigFacelet> {
+    let mask = blocks_mask(&cube, &[slot]);
+
+    cube.apply_mask(mask)
+}
+
+fn blocks_mask(cube: &Cube, squares: &[Slot]) -> CubeMask {
     let mut mask = CubeMask::from_val(false);
 
     mask.keep_centers();
-    mask.keep_white_edges(&cube);
+
+    for &slot in squares {
+        let [a, b] = slot.side_colors(cube);
+        mask.keep_edge(cube, [a, b]);
+        mask.keep_corner(cube, [a, b, &cube.d.cc]);
+        // b is the L or R center
+        mask.keep_edge(cube, [b, &cube.d.cc]);
+    }
+
+    mask
+}
+
+/// Keeps both Roux blocks and the last layer corners; solving this is CMLL
+pub fn to_cmll(cube: Cube) -> Cube<AmbigFacelet> {
+    let mut mask = blocks_mask(&cube, &ALL_SLOTS);
+
+    // the first four are the top corners
+    for (_, facelets) in corners_mut(&cube, &mut mask).into_iter().take(4) {
+        for facelet in facelets {
+            *facelet = true;
+        }
+    }
 
     cube.apply_mask(mask)
 }
 
+/// Keeps the first two layers, the last layer corners, and only the U color of the last layer
+/// edges; solving this is COLL, which solves the corners without messing up edge orientation
+pub fn to_coll(cube: Cube) -> Cube<AmbigFacelet> {
+    let mut mask = cross_and_pairs_mask(&cube, &ALL_SLOTS);
+
+    mask.keep_color(&cube, &cube.u.cc);
+    // the first four are the top corners; the bottom ones are kept already
+    for (_, facelets) in corners_mut(&cube, &mut mask).into_iter().take(4) {
+        for facelet in facelets {
+            *facelet = true;
+        }
+    }
+
+    cube.apply_mask(mask)
+}
+
+/// One edge or corner, named by the faces it belongs between; so the colors it has are the
+/// colors of those centers
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Piece {
+    Edge([Dir; 2]),
+    Corner([Dir; 3]),
+}
+
+const ALL_PIECES: [Piece; 20] = [
+    Piece::Edge([Dir::U, Dir::F]),
+    Piece::Edge([Dir::U, Dir::R]),
+    Piece::Edge([Dir::U, Dir::B]),
+    Piece::Edge([Dir::U, Dir::L]),
+    Piece::Edge([Dir::F, Dir::L]),
+    Piece::Edge([Dir::F, Dir::R]),
+    Piece::Edge([Dir::B, Dir::L]),
+    Piece::Edge([Dir::B, Dir::R]),
+    Piece::Edge([Dir::D, Dir::F]),
+    Piece::Edge([Dir::D, Dir::R]),
+    Piece::Edge([Dir::D, Dir::B]),
+    Piece::Edge([Dir::D, Dir::L]),
+    Piece::Corner([Dir::U, Dir::F, Dir::L]),
+    Piece::Corner([Dir::U, Dir::F, Dir::R]),
+    Piece::Corner([Dir::U, Dir::B, Dir::L]),
+    Piece::Corner([Dir::U, Dir::B, Dir::R]),
+    Piece::Corner([Dir::D, Dir::F, Dir::L]),
+    Piece::Corner([Dir::D, Dir::F, Dir::R]),
+    Piece::Corner([Dir::D, Dir::B, Dir::L]),
+    Piece::Corner([Dir::D, Dir::B, Dir::R]),
+];
+
+fn dir_letter(dir: Dir) -> char {
+    match dir {
+        Dir::U => 'U',
+        Dir::D => 'D',
+        Dir::F => 'F',
+        Dir::B => 'B',
+        Dir::L => 'L',
+        Dir::R => 'R',
+    }
+}
+
+fn letter_dir(c: char) -> Option<Dir> {
+    match c.to_ascii_uppercase() {
+        'U' => Some(Dir::U),
+        'D' => Some(Dir::D),
+        'F' => Some(Dir::F),
+        'B' => Some(Dir::B),
+        'L' => Some(Dir::L),
+        'R' => Some(Dir::R),
+        _ => None,
+    }
+}
+
+impl Piece {
+    fn faces(&self) -> &[Dir] {
+        match self {
+            Piece::Edge(faces) => faces,
+            Piece::Corner(faces) => faces,
+        }
+    }
+
+    /// The face whose color decides whether this piece is oriented: U or D if it has one, and
+    /// otherwise F or B (for the middle edges)
+    fn orientation_face(&self) -> Dir {
+        let faces = self.faces();
+
+        faces
+            .iter()
+            .find(|d| matches!(d, Dir::U | Dir::D))
+            .or_else(|| faces.iter().find(|d| matches!(d, Dir::F | Dir::B)))
+            .copied()
+            .expect("Every piece is on U, D, F or B")
+    }
+}
+
+impl TryFrom<&str> for Piece {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut faces: Vec<Dir> = Vec::new();
+
+        for c in value.chars() {
+            let dir = letter_dir(c)
+                .ok_or_else(|| format!("'{value}' isn't a piece; '{c}' isn't a face"))?;
+            faces.push(dir);
+        }
+
+        // whatever order the faces came in, they have to be one of the real pieces
+        ALL_PIECES
+            .iter()
+            .find(|p| p.faces().len() == faces.len() && p.faces().iter().all(|f| faces.contains(f)))
+            .copied()
+            .ok_or_else(|| format!("'{value}' isn't an edge or a corner"))
+    }
+}
+
+impl Display for Piece {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for &dir in self.faces() {
+            write!(f, "{}", dir_letter(dir))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// How much of a piece has to be right
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Keep {
+    /// In its place, and the right way around
+    Solved,
+    /// Only its U or D facelet (F or B, for the middle edges) has to be on the face of that
+    /// color; so for a last layer piece this is the same as being oriented for OLL
+    Oriented,
+}
+
+/// Description of a mask: which pieces have to be solved, and which only oriented. The centers
+/// are always kept. This is how to describe a new stage without writing a new `to_` function
+/// for it; e.g. "solved: D* FR; oriented: U*" is the same as solving the cross and one pair while
+/// also orienting the last layer.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct MaskSpec {
+    pieces: Vec<(Piece, Keep)>,
+}
+
+impl MaskSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a piece; if it's already there, keeps whichever asks for more
+    pub fn keep(&mut self, piece: Piece, keep: Keep) {
+        match self.pieces.iter_mut().find(|(p, _)| p == &piece) {
+            Some((_, existing)) => {
+                if keep == Keep::Solved {
+                    *existing = Keep::Solved;
+                }
+            }
+            None => {
+                // kept in a fixed order, so the same mask always looks the same
+                self.pieces.push((piece, keep));
+                self.pieces
+                    .sort_by_key(|(p, _)| ALL_PIECES.iter().position(|q| q == p));
+            }
+        }
+    }
+
+    pub fn pieces(&self) -> &[(Piece, Keep)] {
+        &self.pieces
+    }
+
+    /// Breaks this into smaller masks of at most `size` pieces each, in order; solving all of
+    /// them is the same as solving this one
+    pub fn chunks(&self, size: usize) -> Vec<MaskSpec> {
+        self.pieces
+            .chunks(size)
+            .map(|pieces| MaskSpec {
+                pieces: pieces.to_vec(),
+            })
+            .collect()
+    }
+
+    pub fn apply(&self, cube: Cube) -> Cube<AmbigFacelet> {
+        let mut mask = CubeMask::from_val(false);
+
+        mask.keep_centers();
+
+        let color = |dir: Dir| cube.face_grid(dir)[1][1];
+
+        for (piece, keep) in &self.pieces {
+            let oriented = color(piece.orientation_face());
+
+            match (piece, keep) {
+                (Piece::Edge([a, b]), Keep::Solved) => {
+                    mask.keep_edge(&cube, [color(*a), color(*b)])
+                }
+                (Piece::Edge([a, b]), Keep::Oriented) => {
+                    mask.keep_edge_facelet(&cube, [color(*a), color(*b)], oriented)
+                }
+                (Piece::Corner([a, b, c]), Keep::Solved) => {
+                    mask.keep_corner(&cube, [color(*a), color(*b), color(*c)])
+                }
+                (Piece::Corner([a, b, c]), Keep::Oriented) => {
+                    mask.keep_corner_facelet(&cube, [color(*a), color(*b), color(*c)], oriented)
+                }
+            }
+        }
+
+        cube.clone().apply_mask(mask)
+    }
+}
+
+/// The format is one or more parts separated by semicolons, each starting with "solved:" or
+/// "oriented:", then a list of pieces like "DF" or "UFR" (any order of faces). A face with a star,
+/// like "D*", means every piece on that face, and "*" on its own means every piece.
+impl TryFrom<&str> for MaskSpec {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut out = MaskSpec::new();
+
+        for part in value.split(';').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let (keep, pieces) = part
+                .split_once(':')
+                .ok_or_else(|| format!("'{part}' should start with 'solved:' or 'oriented:'"))?;
+
+            let keep = match keep.trim().to_ascii_lowercase().as_str() {
+                "solved" => Keep::Solved,
+                "oriented" => Keep::Oriented,
+                other => return Err(format!("'{other}' should be 'solved' or 'oriented'")),
+            };
+
+            for token in pieces.split(|c: char| c.is_whitespace() || c == ',') {
+                if token.is_empty() {
+                    continue;
+                }
+
+                if token == "*" {
+                    for piece in ALL_PIECES {
+                        out.keep(piece, keep);
+                    }
+                } else if let Some(face) = token.strip_suffix('*') {
+                    let mut letters = face.chars();
+                    let face = match (letters.next(), letters.next()) {
+                        (Some(c), None) => letter_dir(c),
+                        _ => None,
+                    }
+                    .ok_or_else(|| format!("'{token}' should be one face and then a star"))?;
+
+                    for piece in ALL_PIECES.iter().filter(|p| p.faces().contains(&face)) {
+                        out.keep(*piece, keep);
+                    }
+                } else {
+                    out.keep(Piece::try_from(token)?, keep);
+                }
+            }
+        }
+
+        if out.pieces.is_empty() {
+            return Err("Mask should keep at least one piece".to_string());
+        }
+
+        Ok(out)
+    }
+}
+
+impl Display for MaskSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+
+        for (keep, name) in [(Keep::Solved, "solved"), (Keep::Oriented, "oriented")] {
+            let pieces: Vec<String> = self
+                .pieces
+                .iter()
+                .filter(|(_, k)| k == &keep)
+                .map(|(p, _)| p.to_string())
+                .collect();
+
+            if !pieces.is_empty() {
+                parts.push(format!("{name}: {}", pieces.join(" ")));
+            }
+        }
+
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
 #[cfg(test)]
 mod wc_tests {
     // messes up top layer; leaves first two layers alone
-    const OLL_SCRAMBLE: &'static str = "R U2 R' U' R U' R' U'";
+    const OLL_SCRAMBLE: &str = "R U2 R' U' R U' R' U'";
 
     // messes up bottom layer, leaves white cross alone
-    const FL_SCRAMBLE: &'static str = "U F' U' F";
+    const FL_SCRAMBLE: &str = "U F' U' F";
 
     // messes up second layer, leaves bottom layer alone
-    const F2L_SCRAMBLE: &'static str = "U F' U' F U R U' R'";
+    const F2L_SCRAMBLE: &str = "U F' U' F U R U' R'";
 
     // messes up bottom layer, leaves white cross alone (OLL move that i flipped to the bottom)
-    const OBL_SCRAMBLE: &'static str = "R' D2 R D R' D R D";
+    const OBL_SCRAMBLE: &str = "R' D2 R D R' D R D";
 
     // messes up yellow cross, leaves F2L alone
-    const YELLOW_CROSS_SCRAMBLE: &'static str = "F R U R' U' F'";
+    const YELLOW_CROSS_SCRAMBLE: &str = "F R U R' U' F'";
 
     // messes up white cross, leaves other two layers alone
-    const WHITE_CROSS_SCRAMBLE: &'static str = "F L D L' D' F'";
+    const WHITE_CROSS_SCRAMBLE: &str = "F L D L' D' F'";
 
     use super::*;
     use crate::moves::{parse_many, ApplyMove};
@@ -404,4 +935,73 @@ mod wc_tests {
         // we scramble the bottom, so the white cross (which is on top) is unaffected
         test_wc_state(start, WHITE_CROSS_SCRAMBLE, true);
     }
+
+    #[test]
+    fn pairs_mask() {
+        let start = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+
+        // OLL leaves the whole first two layers alone
+        let oll = start.clone().apply_many(&parse_many(OLL_SCRAMBLE));
+        assert!(to_cross_and_pairs(oll, &ALL_SLOTS).is_solved());
+
+        // this one takes out the front right pair, but nothing else
+        let f2l = start.apply_many(&parse_many(FL_SCRAMBLE));
+        assert!(!to_cross_and_pairs(f2l.clone(), &[Slot::FR]).is_solved());
+        assert!(!to_pair(f2l.clone(), Slot::FR).is_solved());
+        assert!(to_cross_and_pairs(f2l.clone(), &[Slot::FL, Slot::BL, Slot::BR]).is_solved());
+        assert!(to_pair(f2l, Slot::BR).is_solved());
+    }
+
+    #[test]
+    fn mask_specs() {
+        let scrambled = Cube::make_solved(Facelet::Green, Facelet::Yellow)
+            .apply_many(&parse_many("R U2 F' L D2 B R' U F2 L' D B2 U'"));
+
+        let spec = |s: &str| MaskSpec::try_from(s).unwrap();
+
+        // the hand-built masks are just particular specs
+        assert_eq!(
+            spec("solved: DF DR DB DL").apply(scrambled.clone()),
+            to_cross(scrambled.clone(), &scrambled.d.cc)
+        );
+        assert_eq!(
+            spec("solved: d* fl, rf BL BR; oriented: U*").apply(scrambled.clone()),
+            to_oll(scrambled.clone())
+        );
+        assert_eq!(
+            spec("solved: DF DR DB DL; solved: RFD FR").apply(scrambled.clone()),
+            to_cross_and_pairs(scrambled, &[Slot::FR])
+        );
+
+        // solved wins over oriented, and the string round trips
+        let s = spec("oriented: *; solved: UF DBL");
+        assert_eq!(s.pieces().len(), 20);
+        assert_eq!(spec(&s.to_string()), s);
+        assert!(s.to_string().starts_with("solved: UF DBL; oriented: UR"));
+
+        for bad in [
+            "",
+            "DF",
+            "solved: UD",
+            "solved: UFF",
+            "oriented: UF*",
+            "kept: UF",
+        ] {
+            assert!(MaskSpec::try_from(bad).is_err(), "{bad}");
+        }
+
+        // orienting only cares about the one facelet: R moves the top right edge into the
+        // slot, but U only moves it around the top
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+        let edge = spec("oriented: UR");
+        assert!(edge
+            .apply(solved.clone().apply_many(&parse_many("U")))
+            .is_solved());
+        assert!(!edge
+            .apply(solved.clone().apply_many(&parse_many("R")))
+            .is_solved());
+        assert!(!spec("solved: UR")
+            .apply(solved.apply_many(&parse_many("U")))
+            .is_solved());
+    }
 }
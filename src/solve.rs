@@ -1,8 +1,11 @@
 //! Module for solving cubes in various ways and to various degrees
 
-use crate::cube::{AmbigFacelet, Cube};
-use crate::moves::{ApplyMove, FullMove, ALL_AMTS, ALL_DIRS};
-use crate::shadow::to_white_cross;
+use crate::cube::{AmbigFacelet, ColorScheme, Cube, Facelet};
+use crate::dfs_util;
+use crate::heuristic_caches::{CappedHeuristicCache, Heuristic, HeuristicCache};
+use crate::moves::{Amt, ApplyMove, Dir, FullMove, ALL_AMTS, ALL_DIRS};
+use crate::shadow::{to_cross, to_pair, to_white_cross, MaskSpec, Slot, ALL_SLOTS};
+use crate::transform::{Axis, Rotation};
 
 type MaskedCube = Cube<AmbigFacelet>;
 
@@ -62,3 +65,428 @@ pub fn solve_wc(cube: Cube) -> Vec<FullMove> {
 
     panic!("idk dude couldn't solve it in {MAX_MOVES} moves, maybe i'm broken")
 }
+
+/// Optimal cross for one color, possibly with some F2L pairs as well; do the rotation (if any)
+/// first, to put the cross color on the bottom, then the moves. The slots are named for the cube
+/// after the rotation.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CrossSolution {
+    pub color: Facelet,
+    pub slots: Vec<Slot>,
+    pub rotation: Option<Rotation>,
+    pub moves: Vec<FullMove>,
+}
+
+/// Heuristic for solving a cross on the D face. Every cube gets recolored to the Western scheme
+/// (with white on D) before searching, so one table covers every cross color.
+pub struct CrossCache {
+    heuristic: CappedHeuristicCache<MaskedCube>,
+}
+
+impl CrossCache {
+    pub fn initialize() -> Self {
+        // the full table is about 190,000 states; this is a good deal smaller and still prunes
+        // nearly everything, since no cross takes more than 8 moves
+        Self::with_cap(4)
+    }
+
+    fn with_cap(cap: usize) -> Self {
+        let goal = to_cross(
+            Cube::make_solved(Facelet::Green, Facelet::Yellow),
+            &Facelet::White,
+        );
+
+        Self {
+            heuristic: CappedHeuristicCache::from_goal(goal, &ALL_DIRS, &[], cap),
+        }
+    }
+}
+
+/// Heuristics for solving a cross plus some F2L pairs. The cross and each pair are searched as
+/// separate masked cubes, each with its own table, and the heuristic is the worst of them. Same
+/// as with [CrossCache], everything is recolored first, so the tables work for any cross color.
+pub struct XCrossCache {
+    cross: CrossCache,
+    // one for each slot, in the order of ALL_SLOTS
+    pairs: Vec<HeuristicCache<MaskedCube>>,
+}
+
+const XCROSS_CAP: usize = 5;
+
+impl XCrossCache {
+    pub fn initialize() -> Self {
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+
+        // each pair is only a corner and an edge, so these are tiny
+        let pairs = ALL_SLOTS
+            .iter()
+            .map(|&slot| HeuristicCache::from_goal(to_pair(solved.clone(), slot), &ALL_DIRS, &[]))
+            .collect();
+
+        Self {
+            cross: CrossCache::with_cap(XCROSS_CAP),
+            pairs,
+        }
+    }
+}
+
+impl XCrossCache {
+    /// Heuristic for the cross alone; see [solve_cross]
+    pub fn cross(&self) -> &CrossCache {
+        &self.cross
+    }
+}
+
+struct XCrossHeuristic<'a> {
+    cache: &'a XCrossCache,
+    slots: &'a [Slot],
+}
+
+impl<'a> Heuristic<Vec<MaskedCube>> for XCrossHeuristic<'a> {
+    fn evaluate(&self, state: &Vec<MaskedCube>) -> usize {
+        // first entry is the cross, then one for each slot
+        let mut out = self.cache.cross.heuristic.evaluate(&state[0]);
+
+        for (slot, pair) in self.slots.iter().zip(state.iter().skip(1)) {
+            let i = ALL_SLOTS.iter().position(|s| s == slot).unwrap();
+            out = out.max(self.cache.pairs[i].evaluate(pair));
+        }
+
+        out
+    }
+}
+
+/// Whole-cube rotation that brings the face with the given center color to the bottom
+pub fn rotation_to_bottom(cube: &Cube, color: &Facelet) -> Option<Rotation> {
+    let (axis, amt) = if &cube.d.cc == color {
+        return None;
+    } else if &cube.b.cc == color {
+        (Axis::X, Amt::One)
+    } else if &cube.f.cc == color {
+        (Axis::X, Amt::Rev)
+    } else if &cube.u.cc == color {
+        (Axis::X, Amt::Two)
+    } else if &cube.r.cc == color {
+        (Axis::Z, Amt::One)
+    } else {
+        (Axis::Z, Amt::Rev)
+    };
+
+    Some(Rotation { axis, amt })
+}
+
+/// Finds the optimal cross of the given color, holding that color on the bottom
+pub fn solve_cross(cube: &Cube, color: &Facelet, cache: &CrossCache) -> CrossSolution {
+    let rotation = rotation_to_bottom(cube, color);
+
+    let held = match rotation {
+        Some(rotation) => cube.rotated(rotation),
+        None => cube.clone(),
+    };
+
+    assert_eq!(&held.d.cc, color, "Cross color should be on the bottom");
+
+    let start = to_cross(held.recolored(&ColorScheme::western()), &Facelet::White);
+
+    // no cross takes more than 8 moves, and the search needs one more than that to find it
+    const MAX_FUEL: usize = 9;
+
+    let moves = dfs_util::solve(
+        start,
+        &ALL_DIRS,
+        &[],
+        |c| c.is_solved(),
+        &cache.heuristic,
+        MAX_FUEL,
+    );
+
+    CrossSolution {
+        color: color.clone(),
+        slots: Vec::new(),
+        rotation,
+        moves,
+    }
+}
+
+/// Finds the optimal cross of the given color along with the pairs in the given slots; so one
+/// slot is an xcross, two is an xxcross, and so on, although more than two gets slow
+pub fn solve_xcross(
+    cube: &Cube,
+    color: &Facelet,
+    slots: &[Slot],
+    cache: &XCrossCache,
+) -> CrossSolution {
+    let rotation = rotation_to_bottom(cube, color);
+
+    let held = match rotation {
+        Some(rotation) => cube.rotated(rotation),
+        None => cube.clone(),
+    };
+
+    let normalized = held.recolored(&ColorScheme::western());
+
+    let mut start = vec![to_cross(normalized.clone(), &Facelet::White)];
+    for &slot in slots {
+        start.push(to_pair(normalized.clone(), slot));
+    }
+
+    // optimal xxcrosses are usually 10 or 11 moves; this leaves a little room
+    const MAX_FUEL: usize = 15;
+
+    let heuristic = XCrossHeuristic { cache, slots };
+
+    let moves = dfs_util::solve(
+        start,
+        &ALL_DIRS,
+        &[],
+        |state: &Vec<MaskedCube>| state.iter().all(|c| c.is_solved()),
+        &heuristic,
+        MAX_FUEL,
+    );
+
+    CrossSolution {
+        color: color.clone(),
+        slots: slots.to_vec(),
+        rotation,
+        moves,
+    }
+}
+
+/// Optimal cross for each of the six colors, in the order U D F B L R of the cube as it's held
+pub fn solve_all_crosses(cube: &Cube, cache: &CrossCache) -> Vec<CrossSolution> {
+    [
+        &cube.u.cc, &cube.d.cc, &cube.f.cc, &cube.b.cc, &cube.l.cc, &cube.r.cc,
+    ]
+    .into_iter()
+    .map(|color| solve_cross(cube, color, cache))
+    .collect()
+}
+
+/// The shortest of the given crosses; ties go to whichever comes first
+pub fn best_cross(solutions: &[CrossSolution]) -> &CrossSolution {
+    solutions
+        .iter()
+        .min_by_key(|sol| sol.moves.len())
+        .expect("Should have at least one cross to pick from")
+}
+
+/// Heuristic for solving any [MaskSpec]. The mask is split into a few pieces at a time, each
+/// with its own table, and the heuristic is the worst of them; like the cross caches, cubes get
+/// recolored to the Western scheme first, so this works however the cube is colored.
+pub struct MaskCache {
+    parts: Vec<(MaskSpec, HeuristicCache<MaskedCube>)>,
+    /// The faces the search (and the tables) can turn
+    dirs: Vec<Dir>,
+}
+
+// three pieces is at most about 10,000 states per table, which is quick to build
+const MASK_CHUNK: usize = 3;
+
+impl MaskCache {
+    pub fn initialize(spec: &MaskSpec) -> Self {
+        Self::from_parts(spec.chunks(MASK_CHUNK), &ALL_DIRS)
+    }
+
+    /// Same idea, but with the parts picked by hand, and only turning the given faces. With
+    /// fewer faces there are fewer states, so bigger parts (and a better heuristic) are fine.
+    pub fn from_parts(parts: Vec<MaskSpec>, dirs: &[Dir]) -> Self {
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+
+        let parts = parts
+            .into_iter()
+            .map(|part| {
+                let goal = part.apply(solved.clone());
+                (part, HeuristicCache::from_goal(goal, dirs, &[]))
+            })
+            .collect();
+
+        Self {
+            parts,
+            dirs: dirs.to_vec(),
+        }
+    }
+}
+
+impl Heuristic<Vec<MaskedCube>> for MaskCache {
+    fn evaluate(&self, state: &Vec<MaskedCube>) -> usize {
+        self.parts
+            .iter()
+            .zip(state.iter())
+            .map(|((_, table), part)| table.evaluate(part))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Finds the shortest way to solve everything in the cache's mask, holding the cube as it is and
+/// only turning the faces the cache was made with. Panics if that takes more than `max_moves`;
+/// big masks get slow well before then.
+pub fn solve_mask(cube: &Cube, cache: &MaskCache, max_moves: usize) -> Vec<FullMove> {
+    let normalized = cube.recolored(&ColorScheme::western());
+
+    let start: Vec<MaskedCube> = cache
+        .parts
+        .iter()
+        .map(|(part, _)| part.apply(normalized.clone()))
+        .collect();
+
+    dfs_util::solve(
+        start,
+        &cache.dirs,
+        &[],
+        |state: &Vec<MaskedCube>| state.iter().all(|c| c.is_solved()),
+        cache,
+        max_moves + 1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::moves::parse_many;
+    use crate::shadow::to_cross_and_pairs;
+
+    use super::*;
+
+    fn scrambled(scramble: &str) -> Cube {
+        Cube::make_solved(Facelet::Green, Facelet::White).apply_many(&parse_many(scramble))
+    }
+
+    /// Applies the solution to the cube, and checks the cross really is solved
+    fn check_cross(cube: &Cube, sol: &CrossSolution) {
+        let mut held = cube.clone();
+        if let Some(rotation) = sol.rotation {
+            held = held.rotated(rotation);
+        }
+
+        assert_eq!(held.d.cc, sol.color);
+
+        let done = to_cross(held.apply_many(&sol.moves), &sol.color);
+        assert!(done.is_solved(), "{:?} cross should be solved", sol.color);
+    }
+
+    #[test]
+    fn matches_white_cross_solver() {
+        let cache = CrossCache::initialize();
+
+        // yellow top means white is already on the bottom
+        let input = "U2 F L D L' D' F'";
+        let cube =
+            Cube::make_solved(Facelet::Green, Facelet::Yellow).apply_many(&parse_many(input));
+
+        let sol = solve_cross(&cube, &Facelet::White, &cache);
+        assert_eq!(sol.rotation, None);
+        assert_eq!(sol.moves.len(), solve_wc(cube.clone()).len());
+        check_cross(&cube, &sol);
+    }
+
+    #[test]
+    fn every_color() {
+        let cache = CrossCache::initialize();
+        let cube = scrambled("B U F' L U R' L' F2 D' F2 L F' R' D L' D U2 R' U2 F' D' R2 F2 B' U2");
+
+        let all = solve_all_crosses(&cube, &cache);
+        assert_eq!(all.len(), 6);
+
+        for sol in &all {
+            check_cross(&cube, sol);
+            assert!(sol.moves.len() <= 8);
+        }
+
+        let best = best_cross(&all);
+        assert!(all.iter().all(|sol| sol.moves.len() >= best.moves.len()));
+    }
+
+    #[test]
+    fn short_crosses() {
+        let cache = CrossCache::initialize();
+
+        // white top, so the yellow cross is on the bottom and one move away
+        let cube = scrambled("D");
+        let sol = solve_cross(&cube, &Facelet::Yellow, &cache);
+        assert_eq!(sol.rotation, None);
+        assert_eq!(sol.moves, parse_many("D'"));
+
+        // the white cross is on top and only needs rotating down
+        let sol = solve_cross(&cube, &Facelet::White, &cache);
+        assert_eq!(sol.rotation, Rotation::try_from("x2").ok());
+        assert!(sol.moves.is_empty());
+
+        // turning the orange side leaves only the red cross alone
+        let cube = scrambled("L");
+        let all = solve_all_crosses(&cube, &cache);
+        let best = best_cross(&all);
+        assert_eq!(best.moves.len(), 0);
+        assert_eq!(best.color, Facelet::Red);
+    }
+
+    #[test]
+    fn xcross_undoes_short_scrambles() {
+        let cache = XCrossCache::initialize();
+
+        // white is on the bottom already, and these only disturb some of the F2L
+        for (scramble, slots) in [
+            ("R U R' F2 D", vec![Slot::FR]),
+            ("L' B2 U F", vec![Slot::BL]),
+            ("R U R' F2 D", vec![Slot::FR, Slot::BL]),
+            ("B' R2 U L' F D2", vec![Slot::FL, Slot::BR]),
+        ] {
+            let moves = parse_many(scramble);
+            let cube = Cube::make_solved(Facelet::Green, Facelet::Yellow).apply_many(&moves);
+
+            let sol = solve_xcross(&cube, &Facelet::White, &slots, &cache);
+
+            assert_eq!(sol.rotation, None);
+            assert!(sol.moves.len() <= moves.len());
+            assert!(
+                to_cross_and_pairs(cube.apply_many(&sol.moves), &slots).is_solved(),
+                "Solving {scramble} for {slots:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn xcross_any_color() {
+        let cache = XCrossCache::initialize();
+
+        // scramble a cube held with red on the bottom, then tip it over; the solver should
+        // tip it back, and undoing the scramble is one way to solve from there
+        let setup = parse_many("F R' D2 L B' U'");
+        let z = Rotation::try_from("z").unwrap();
+        let cube = Cube::make_solved(Facelet::Green, Facelet::Orange)
+            .apply_many(&setup)
+            .rotated(z);
+
+        let sol = solve_xcross(&cube, &Facelet::Red, &[Slot::FR], &cache);
+
+        assert_eq!(sol.rotation, Some(z.inverse()));
+        assert!(sol.moves.len() <= setup.len());
+
+        let held = cube.rotated(z.inverse());
+        assert_eq!(held.d.cc, Facelet::Red);
+        assert!(to_cross_and_pairs(held.apply_many(&sol.moves), &[Slot::FR]).is_solved());
+    }
+
+    #[test]
+    fn any_mask() {
+        let cross_cache = CrossCache::initialize();
+
+        let cube = scrambled("R U2 F' L D2 B R' U F2 L' D B2 U'");
+        let spec = MaskSpec::try_from("solved: DF DR DB DL").unwrap();
+        let cache = MaskCache::initialize(&spec);
+
+        // white is on top here, so this is the yellow cross, but either way it's a cross
+        let moves = solve_mask(&cube, &cache, 8);
+        let cross = solve_cross(&cube, &cube.d.cc, &cross_cache);
+        assert_eq!(moves.len(), cross.moves.len());
+        assert!(spec.apply(cube.apply_many(&moves)).is_solved());
+
+        // a sune case is just OLL, which sune (or antisune) is an optimal solution for
+        let spec = MaskSpec::try_from("solved: D* FL FR BL BR; oriented: U*").unwrap();
+        let cache = MaskCache::initialize(&spec);
+        let cube = scrambled("R U R' U R U2 R'");
+
+        let moves = solve_mask(&cube, &cache, 7);
+        assert_eq!(moves.len(), 7);
+        assert!(spec.apply(cube.apply_many(&moves)).is_solved());
+    }
+}
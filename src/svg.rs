@@ -0,0 +1,250 @@
+//! Module for drawing a cube as an SVG image. There are three views:
+//!
+//! - the unfolded net, laid out like [crate::render::render_net]
+//! - an isometric view of the U, F and R faces
+//! - a top-down view of the last layer, with the top row of each side face drawn as a thin
+//!   strip around the U face; this is the usual way to draw OLL and PLL cases
+//!
+//! Masked cubes are fine too; unknown facelets are drawn in gray.
+
+use std::fmt::Write;
+
+use crate::cube::Cube;
+use crate::moves::Dir;
+use crate::render::Sticker;
+
+/// Side length of a facelet, in pixels
+const CELL: f64 = 30.0;
+
+/// Blank space around the whole drawing, in pixels
+const MARGIN: f64 = 10.0;
+
+fn svg_document(width: f64, height: f64, body: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" \
+         width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n{body}</svg>\n"
+    )
+}
+
+fn rect(out: &mut String, x: f64, y: f64, w: f64, h: f64, fill: &str) {
+    writeln!(
+        out,
+        "  <rect x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\" fill=\"{fill}\" \
+         stroke=\"#000000\" stroke-width=\"2\"/>"
+    )
+    .unwrap();
+}
+
+fn polygon(out: &mut String, points: &[(f64, f64)], fill: &str) {
+    let points: Vec<String> = points
+        .iter()
+        .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+        .collect();
+
+    writeln!(
+        out,
+        "  <polygon points=\"{}\" fill=\"{fill}\" stroke=\"#000000\" stroke-width=\"2\" \
+         stroke-linejoin=\"round\"/>",
+        points.join(" ")
+    )
+    .unwrap();
+}
+
+/// Draws the unfolded net; U on top, then L F R B across the middle, then D on the bottom
+pub fn net_svg<F: Sticker>(cube: &Cube<F>) -> String {
+    // a little space between faces, so the net reads as six separate faces
+    let gap = CELL / 4.0;
+    let face_size = 3.0 * CELL + gap;
+
+    // position of each face in the net, in units of faces
+    let layout = [
+        (Dir::U, 1.0, 0.0),
+        (Dir::L, 0.0, 1.0),
+        (Dir::F, 1.0, 1.0),
+        (Dir::R, 2.0, 1.0),
+        (Dir::B, 3.0, 1.0),
+        (Dir::D, 1.0, 2.0),
+    ];
+
+    let mut body = String::new();
+
+    for (face, face_x, face_y) in layout {
+        let grid = cube.face_grid(face);
+
+        for (row, facelets) in grid.iter().enumerate() {
+            for (col, facelet) in facelets.iter().enumerate() {
+                let x = MARGIN + face_x * face_size + col as f64 * CELL;
+                let y = MARGIN + face_y * face_size + row as f64 * CELL;
+                rect(&mut body, x, y, CELL, CELL, facelet.hex_color());
+            }
+        }
+    }
+
+    let width = 2.0 * MARGIN + 4.0 * face_size - gap;
+    let height = 2.0 * MARGIN + 3.0 * face_size - gap;
+
+    svg_document(width, height, &body)
+}
+
+/// Draws the cube in 3D from above the UFR corner, so the U, F and R faces are visible
+pub fn isometric_svg<F: Sticker>(cube: &Cube<F>) -> String {
+    // the cube goes from 0 to 3 on each axis; x from L to R, y from D to U, z from B to F
+    let cos30 = 3.0_f64.sqrt() / 2.0;
+    let project = |x: f64, y: f64, z: f64| {
+        (
+            MARGIN + (x - z + 3.0) * cos30 * CELL,
+            MARGIN + ((x + z) / 2.0 - y + 3.0) * CELL,
+        )
+    };
+
+    let mut body = String::new();
+
+    // each face is a function from (row, col, corner offsets) to a point in space
+    type FacePoint = fn(f64, f64) -> (f64, f64, f64);
+    let faces: [(Dir, FacePoint); 3] = [
+        // U seen from above has B at the top, so rows go from back to front
+        (Dir::U, |r, c| (c, 3.0, r)),
+        (Dir::F, |r, c| (c, 3.0 - r, 3.0)),
+        // R seen from the right has F on the left, so columns go from front to back
+        (Dir::R, |r, c| (3.0, 3.0 - r, 3.0 - c)),
+    ];
+
+    for (face, point) in faces {
+        let grid = cube.face_grid(face);
+
+        for (row, facelets) in grid.iter().enumerate() {
+            for (col, facelet) in facelets.iter().enumerate() {
+                let (r, c) = (row as f64, col as f64);
+                let corners: Vec<(f64, f64)> =
+                    [(r, c), (r, c + 1.0), (r + 1.0, c + 1.0), (r + 1.0, c)]
+                        .iter()
+                        .map(|&(r, c)| {
+                            let (x, y, z) = point(r, c);
+                            project(x, y, z)
+                        })
+                        .collect();
+                polygon(&mut body, &corners, facelet.hex_color());
+            }
+        }
+    }
+
+    let width = 2.0 * MARGIN + 6.0 * cos30 * CELL;
+    let height = 2.0 * MARGIN + 6.0 * CELL;
+
+    svg_document(width, height, &body)
+}
+
+/// Draws the U face from above, with the top row of each side face drawn as a strip along the
+/// matching edge; the standard picture for a last layer case
+pub fn last_layer_svg<F: Sticker>(cube: &Cube<F>) -> String {
+    let strip = CELL / 3.0;
+    let gap = CELL / 8.0;
+    // where the U face starts, in both directions
+    let start = MARGIN + strip + gap;
+
+    let mut body = String::new();
+
+    for (row, facelets) in cube.face_grid(Dir::U).iter().enumerate() {
+        for (col, facelet) in facelets.iter().enumerate() {
+            let x = start + col as f64 * CELL;
+            let y = start + row as f64 * CELL;
+            rect(&mut body, x, y, CELL, CELL, facelet.hex_color());
+        }
+    }
+
+    // the top rows of the side faces, ordered the way they appear from above; that's left to
+    // right for the B and F strips, and back to front for the L and R strips
+    let b_row = cube.face_grid(Dir::B)[0];
+    let f_row = cube.face_grid(Dir::F)[0];
+    let l_row = cube.face_grid(Dir::L)[0];
+    let r_row = cube.face_grid(Dir::R)[0];
+
+    let far = start + 3.0 * CELL + gap;
+
+    for i in 0..3 {
+        let along = start + i as f64 * CELL;
+
+        rect(
+            &mut body,
+            along,
+            MARGIN,
+            CELL,
+            strip,
+            b_row[2 - i].hex_color(),
+        );
+        rect(&mut body, along, far, CELL, strip, f_row[i].hex_color());
+        rect(&mut body, MARGIN, along, strip, CELL, l_row[i].hex_color());
+        rect(&mut body, far, along, strip, CELL, r_row[2 - i].hex_color());
+    }
+
+    let size = 2.0 * MARGIN + 3.0 * CELL + 2.0 * (strip + gap);
+
+    svg_document(size, size, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cube::{AmbigFacelet, Facelet};
+    use crate::moves::{parse_many, ApplyMove};
+    use crate::shadow::to_white_cross;
+
+    use super::*;
+
+    fn fills(svg: &str) -> Vec<&str> {
+        svg.split("fill=\"")
+            .skip(1)
+            .map(|rest| &rest[..rest.find('"').unwrap()])
+            .collect()
+    }
+
+    #[test]
+    fn net_has_every_facelet() {
+        let cube = Cube::make_solved(Facelet::Green, Facelet::White);
+        let svg = net_svg(&cube);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+
+        let fills = fills(&svg);
+        assert_eq!(fills.len(), 54);
+        for color in [&Facelet::White, &Facelet::Green, &Facelet::Red] {
+            assert_eq!(fills.iter().filter(|&&f| f == color.hex_color()).count(), 9);
+        }
+    }
+
+    #[test]
+    fn isometric_has_three_faces() {
+        let cube = Cube::make_solved(Facelet::Green, Facelet::White);
+        let svg = isometric_svg(&cube);
+        let fills = fills(&svg);
+
+        assert_eq!(fills.len(), 27);
+        assert_eq!(fills.iter().filter(|&&f| f == "#ffffff").count(), 9);
+        assert_eq!(fills.iter().filter(|&&f| f == "#009e60").count(), 9);
+        assert_eq!(fills.iter().filter(|&&f| f == "#c41e3a").count(), 9);
+    }
+
+    #[test]
+    fn last_layer_strips() {
+        // T-perm, in WCA orientation; the U face stays white but the sides get swapped
+        let cube = Cube::make_solved(Facelet::Green, Facelet::White)
+            .apply_many(&parse_many("R U R' U' R' F R2 U' R' U' R U R' F'"));
+        let svg = last_layer_svg(&cube);
+        let fills = fills(&svg);
+
+        assert_eq!(fills.len(), 21);
+        assert!(fills[..9].iter().all(|&f| f == "#ffffff"));
+    }
+
+    #[test]
+    fn masked_facelets_are_gray() {
+        let cube = to_white_cross(Cube::make_solved(Facelet::Green, Facelet::White));
+        let svg = net_svg(&cube);
+        let fills = fills(&svg);
+
+        assert_eq!(fills.len(), 54);
+        // four edges (two facelets each) and six centers are kept
+        let gray = AmbigFacelet::Unknown.hex_color();
+        assert_eq!(fills.iter().filter(|&&f| f == gray).count(), 54 - 14);
+    }
+}
@@ -62,12 +62,14 @@
 //! G4 -- this is just "cube is solved." Easy peasy.
 
 pub use g0g1::{solve_to_g1, G0toG1Cache};
-pub use g1g2::{solve_to_g2, G1toG2Cache};
+pub use g1g2::{solve_to_g2, G1State, G1toG2Cache};
 pub use g2g3::{solve_to_g3, G2toG3Cache};
 pub use g3g4::{solve_to_g4, G3toG4Cache};
 
 use crate::cube::Cube;
+use crate::edge_orientation_state::EdgeOrientationState;
 use crate::moves::{ApplyMove, FullMove};
+use crate::playback::Phase;
 
 mod g0g1;
 mod g1g2;
@@ -109,3 +111,50 @@ pub fn full_solve(cube: &Cube, cache: &ThistlethwaiteCaches) -> Vec<FullMove> {
     full_solution.extend(g4_solution);
     full_solution
 }
+
+/// Same as [full_solve], but split up by phase, with what each phase achieved. Checks each
+/// invariant really does hold once its phase is done.
+pub fn solve_in_phases(cube: &Cube, cache: &ThistlethwaiteCaches) -> Vec<Phase> {
+    let g1_solution = solve_to_g1(cube, &cache.g0g1cache);
+    let g1_solved = cube.clone().apply_many(&g1_solution);
+    assert!(EdgeOrientationState::from_cube(&g1_solved).is_solved());
+
+    let g2_solution = solve_to_g2(&g1_solved, &cache.g1g2cache);
+    let g2_solved = g1_solved.apply_many(&g2_solution);
+    assert!(G1State::from_cube(&g2_solved).is_solved());
+
+    let g3_solution = solve_to_g3(&g2_solved, &cache.g2g3cache);
+    let g3_solved = g2_solved.apply_many(&g3_solution);
+    assert!(cache.g2g3cache.contains(&g3_solved));
+
+    let g4_solution = solve_to_g4(&g3_solved, &cache.g3g4cache);
+    assert!(g3_solved.apply_many(&g4_solution).is_solved());
+
+    vec![
+        Phase {
+            reached: "G1".to_string(),
+            invariant: "Every edge is oriented, so U and D only need half turns from here"
+                .to_string(),
+            moves: g1_solution,
+        },
+        Phase {
+            reached: "G2".to_string(),
+            invariant: "Every corner is oriented and the M slice edges are in the M slice, so \
+                        F and B only need half turns from here too"
+                .to_string(),
+            moves: g2_solution,
+        },
+        Phase {
+            reached: "G3".to_string(),
+            invariant: "Every piece is somewhere half turns can bring it home, so only half \
+                        turns from here"
+                .to_string(),
+            moves: g3_solution,
+        },
+        Phase {
+            reached: "G4".to_string(),
+            invariant: "Solved".to_string(),
+            moves: g4_solution,
+        },
+    ]
+}
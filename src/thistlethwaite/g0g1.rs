@@ -5,7 +5,6 @@ use crate::heuristic_caches::HeuristicCache;
 use crate::moves::{FullMove, ALL_DIRS};
 
 /// Invariants from a cube in G0 to describe what's left to get to G1
-
 pub struct G0toG1Cache {
     heuristic_cache: HeuristicCache<EdgeOrientationState>,
 }
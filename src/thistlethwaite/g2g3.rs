@@ -68,6 +68,15 @@ impl G2toG3Cache {
     }
 }
 
+impl G2toG3Cache {
+    /// Whether the cube is in G3, that is, solvable with only half turns
+    pub fn contains(&self, cube: &Cube) -> bool {
+        let state = CubePositions::from_cube(cube);
+
+        self.edges.contains(&state.edges) && self.corners.contains(&state.corners)
+    }
+}
+
 impl Heuristic<CubePositions> for G2toG3Cache {
     fn evaluate(&self, state: &CubePositions) -> usize {
         let e = self.edge_heuristic.evaluate(&state.edges);
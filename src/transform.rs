@@ -0,0 +1,561 @@
+//! Module for transforming algorithms into other algorithms. `moves::invert` is the simplest
+//! case of this; here we also have mirroring across a slice plane (e.g. R U R' becomes L' U' L)
+//! and conjugating by a whole-cube rotation (e.g. R U R' done "from the back" is B U B').
+//!
+//! Each transformation also has a counterpart that acts on a [Cube] directly, by moving the
+//! facelets around in space. That's mostly useful for checking the algorithm-level
+//! transformations against the cube model, since nothing here is especially obvious.
+
+use std::fmt::{Display, Formatter};
+
+use crate::cube::{Cube, Facelet};
+use crate::moves::{Amt, Dir, FullMove};
+
+/// Axis for a whole-cube rotation; X follows R, Y follows U, and Z follows F
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Rotation {
+    pub axis: Axis,
+    pub amt: Amt,
+}
+
+/// Plane to mirror across, named for the slice which sits in it. M is between L and R, E is
+/// between U and D, and S is between F and B.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Plane {
+    M,
+    E,
+    S,
+}
+
+// Coordinates are x going from L to R, y going from D to U, and z going from B to F; every
+// facelet is described by the position of its cubelet, plus the direction it's facing
+type Vec3 = [i8; 3];
+
+impl Rotation {
+    pub fn inverse(self) -> Self {
+        let amt = match self.amt {
+            Amt::One => Amt::Rev,
+            Amt::Two => Amt::Two,
+            Amt::Rev => Amt::One,
+        };
+
+        Self {
+            axis: self.axis,
+            amt,
+        }
+    }
+
+    fn apply_vec(self, v: Vec3) -> Vec3 {
+        let quarter_turns = match self.amt {
+            Amt::One => 1,
+            Amt::Two => 2,
+            Amt::Rev => 3,
+        };
+
+        let mut out = v;
+
+        for _ in 0..quarter_turns {
+            let [x, y, z] = out;
+            out = match self.axis {
+                // F goes to U goes to B
+                Axis::X => [x, z, -y],
+                // F goes to L goes to B
+                Axis::Y => [-z, y, x],
+                // U goes to R goes to D
+                Axis::Z => [y, -x, z],
+            };
+        }
+
+        out
+    }
+}
+
+impl Plane {
+    fn apply_vec(self, v: Vec3) -> Vec3 {
+        let [x, y, z] = v;
+        match self {
+            Plane::M => [-x, y, z],
+            Plane::E => [x, -y, z],
+            Plane::S => [x, y, -z],
+        }
+    }
+}
+
+impl Display for Rotation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.axis {
+            Axis::X => write!(f, "x")?,
+            Axis::Y => write!(f, "y")?,
+            Axis::Z => write!(f, "z")?,
+        }
+
+        match self.amt {
+            Amt::One => {}
+            Amt::Two => write!(f, "2")?,
+            Amt::Rev => write!(f, "'")?,
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Rotation {
+    type Error = &'a str;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let mut chars = value.chars();
+
+        let axis = match chars.next() {
+            Some('x') => Axis::X,
+            Some('y') => Axis::Y,
+            Some('z') => Axis::Z,
+            _ => return Err(value),
+        };
+
+        let amt = match chars.as_str() {
+            "" => Amt::One,
+            "2" => Amt::Two,
+            "'" => Amt::Rev,
+            _ => return Err(value),
+        };
+
+        Ok(Rotation { axis, amt })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Plane {
+    type Error = &'a str;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value {
+            "M" => Ok(Plane::M),
+            "E" => Ok(Plane::E),
+            "S" => Ok(Plane::S),
+            other => Err(other),
+        }
+    }
+}
+
+fn dir_to_vec(dir: Dir) -> Vec3 {
+    match dir {
+        Dir::R => [1, 0, 0],
+        Dir::L => [-1, 0, 0],
+        Dir::U => [0, 1, 0],
+        Dir::D => [0, -1, 0],
+        Dir::F => [0, 0, 1],
+        Dir::B => [0, 0, -1],
+    }
+}
+
+fn vec_to_dir(v: Vec3) -> Dir {
+    match v {
+        [1, 0, 0] => Dir::R,
+        [-1, 0, 0] => Dir::L,
+        [0, 1, 0] => Dir::U,
+        [0, -1, 0] => Dir::D,
+        [0, 0, 1] => Dir::F,
+        [0, 0, -1] => Dir::B,
+        other => panic!("Vector {other:?} is not a face direction"),
+    }
+}
+
+/// Position (of the cubelet) of a facelet given in [Cube::face_grid] coordinates
+fn facelet_pos(face: Dir, row: usize, col: usize) -> Vec3 {
+    let r = row as i8;
+    let c = col as i8;
+
+    match face {
+        Dir::U => [c - 1, 1, r - 1],
+        Dir::D => [c - 1, -1, 1 - r],
+        Dir::F => [c - 1, 1 - r, 1],
+        Dir::B => [1 - c, 1 - r, -1],
+        Dir::L => [-1, 1 - r, c - 1],
+        Dir::R => [1, 1 - r, 1 - c],
+    }
+}
+
+/// Inverse of [facelet_pos]; gives the row and column of the facelet at the given position
+/// on the given face
+fn facelet_coords(face: Dir, pos: Vec3) -> (usize, usize) {
+    let [x, y, z] = pos;
+
+    let (r, c) = match face {
+        Dir::U => (z + 1, x + 1),
+        Dir::D => (1 - z, x + 1),
+        Dir::F => (1 - y, x + 1),
+        Dir::B => (1 - y, 1 - x),
+        Dir::L => (1 - y, z + 1),
+        Dir::R => (1 - y, 1 - z),
+    };
+
+    (r as usize, c as usize)
+}
+
+/// Moves every facelet of the cube to a new spot. The supplied function should give the
+/// _inverse_ of the desired motion, since each spot is filled by asking where it came from.
+fn pull_back<F: Clone, Func: Fn(Vec3) -> Vec3>(cube: &Cube<F>, preimage: Func) -> Cube<F> {
+    Cube::from_fn(|face, row, col| {
+        let pos = preimage(facelet_pos(face, row, col));
+        let old_face = vec_to_dir(preimage(dir_to_vec(face)));
+        let (old_row, old_col) = facelet_coords(old_face, pos);
+        cube.face_grid(old_face)[old_row][old_col].clone()
+    })
+}
+
+impl<F: Clone> Cube<F> {
+    /// Physically rotates the whole cube; e.g. after a y rotation, the old R face is in front.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        let inv = rotation.inverse();
+        pull_back(self, |v| inv.apply_vec(v))
+    }
+
+    /// Reflects the whole cube across the given plane. Note the result is not a cube you could
+    /// actually hold in your hand, but the facelet model doesn't mind.
+    pub fn mirrored(&self, plane: Plane) -> Self {
+        pull_back(self, |v| plane.apply_vec(v))
+    }
+}
+
+/// Mirrors an algorithm across the given plane. Faces are reflected across the plane, and since
+/// a mirror swaps clockwise and counterclockwise, every turn is reversed; so across M,
+/// R U R' becomes L' U' L.
+pub fn mirror(fms: &[FullMove], plane: Plane) -> Vec<FullMove> {
+    fms.iter()
+        .map(|fm| {
+            let amt = match fm.amt {
+                Amt::One => Amt::Rev,
+                Amt::Two => Amt::Two,
+                Amt::Rev => Amt::One,
+            };
+
+            FullMove {
+                dir: vec_to_dir(plane.apply_vec(dir_to_vec(fm.dir))),
+                amt,
+            }
+        })
+        .collect()
+}
+
+/// Conjugates an algorithm by a whole-cube rotation. That is, gives the algorithm which does
+/// the same thing as rotating the cube, performing the original, then rotating back. So for
+/// instance R U R' with a y rotation becomes B U B', since after a y, the old B face is on R.
+pub fn rotate(fms: &[FullMove], rotation: Rotation) -> Vec<FullMove> {
+    let inv = rotation.inverse();
+
+    fms.iter()
+        .map(|fm| FullMove {
+            dir: vec_to_dir(inv.apply_vec(dir_to_vec(fm.dir))),
+            amt: fm.amt,
+        })
+        .collect()
+}
+
+/// Amount of turning `amt` times in the direction `unit`; so a unit of Rev done twice is Two
+fn times(unit: Amt, amt: Amt) -> Amt {
+    match (unit, amt) {
+        (_, Amt::Two) => Amt::Two,
+        (Amt::One, amt) => amt,
+        (Amt::Rev, Amt::One) => Amt::Rev,
+        (Amt::Rev, Amt::Rev) => Amt::One,
+        (Amt::Two, _) => Amt::Two,
+    }
+}
+
+/// Splits a token which isn't a face turn or a rotation into face turns and a rotation to do
+/// after them. A wide turn is the opposite face turned the same way, then the whole cube; e.g.
+/// r is L x. A slice turn is the two faces around it, then the whole cube the other way; e.g.
+/// M is R L' x'.
+fn expand_token(token: &str) -> Option<(Vec<FullMove>, Rotation)> {
+    let (base, amt) = if let Some(base) = token.strip_suffix('\'') {
+        (base, Amt::Rev)
+    } else if let Some(base) = token.strip_suffix('2') {
+        (base, Amt::Two)
+    } else {
+        (token, Amt::One)
+    };
+
+    use Amt::{One, Rev};
+    use Dir::*;
+
+    // faces to turn (with the direction of a single turn), then the rotation axis and direction
+    let (faces, axis, unit): (&[(Dir, Amt)], Axis, Amt) = match base {
+        "r" | "Rw" => (&[(L, One)], Axis::X, One),
+        "l" | "Lw" => (&[(R, One)], Axis::X, Rev),
+        "u" | "Uw" => (&[(D, One)], Axis::Y, One),
+        "d" | "Dw" => (&[(U, One)], Axis::Y, Rev),
+        "f" | "Fw" => (&[(B, One)], Axis::Z, One),
+        "b" | "Bw" => (&[(F, One)], Axis::Z, Rev),
+        "M" => (&[(R, One), (L, Rev)], Axis::X, Rev),
+        "E" => (&[(U, One), (D, Rev)], Axis::Y, Rev),
+        "S" => (&[(F, Rev), (B, One)], Axis::Z, One),
+        _ => return None,
+    };
+
+    let moves = faces
+        .iter()
+        .map(|&(dir, unit)| FullMove {
+            dir,
+            amt: times(unit, amt),
+        })
+        .collect();
+
+    let rotation = Rotation {
+        axis,
+        amt: times(unit, amt),
+    };
+
+    Some((moves, rotation))
+}
+
+/// Parses an algorithm in the usual notation; on top of face turns, this allows wide turns
+/// (r or Rw), slice turns (M, E and S) and whole-cube rotations (x, y and z). A [Cube] never
+/// moves its centers, so everything is rewritten as face turns which do the same thing relative
+/// to the centers, and any rotation left over at the end is dropped. So for instance r U r'
+/// becomes L F L'. Panics on bad input because this is not a production application.
+pub fn parse_alg(input: &str) -> Vec<FullMove> {
+    parse_alg_with_frame(input).0
+}
+
+/// The rotations [parse_alg] drops at the end of an algorithm, in order; doing the inverse of
+/// these afterwards turns the cube back to how it was held at the start. For instance, after
+/// r U R' there's an x left over; the r really turned the M slice, and the cube needs an x' to
+/// get its centers back where [parse_alg] says they are. Empty if nothing is left over.
+pub fn leftover_rotations(input: &str) -> Vec<Rotation> {
+    let frame = parse_alg_with_frame(input).1;
+
+    // the rotations might cancel out, which is easiest to see on a cube
+    let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+    let turned = frame
+        .iter()
+        .fold(solved.clone(), |cube, &rotation| cube.rotated(rotation));
+
+    if turned == solved {
+        Vec::new()
+    } else {
+        frame
+    }
+}
+
+fn parse_alg_with_frame(input: &str) -> (Vec<FullMove>, Vec<Rotation>) {
+    // every rotation so far, in order; each face turn gets conjugated by all of them
+    let mut frame: Vec<Rotation> = Vec::new();
+    let mut out = Vec::new();
+
+    for token in input.split_ascii_whitespace() {
+        let (moves, rotation) = if let Ok(fm) = FullMove::try_from(token) {
+            (vec![fm], None)
+        } else if let Ok(rotation) = Rotation::try_from(token) {
+            (Vec::new(), Some(rotation))
+        } else if let Some((moves, rotation)) = expand_token(token) {
+            (moves, Some(rotation))
+        } else {
+            panic!("Bad input: {token}");
+        };
+
+        let mut moves = moves;
+        for &rotation in frame.iter().rev() {
+            moves = rotate(&moves, rotation);
+        }
+        out.extend(moves);
+
+        if let Some(rotation) = rotation {
+            frame.push(rotation);
+        }
+    }
+
+    (out, frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cube::Facelet;
+    use crate::moves::{parse_many, ApplyMove};
+
+    use super::*;
+
+    const ALL_PLANES: [Plane; 3] = [Plane::M, Plane::E, Plane::S];
+
+    const TEST_ALGS: [&str; 4] = [
+        "R U R' U'",
+        "F R U R' U' F'",
+        "R U2 R' U' R U' R' U'",
+        "B U F' L U R' L' F2 D' F2 L F' R' D L' D U2 R' U2 F' D' R2 F2 B' U2",
+    ];
+
+    fn all_rotations() -> Vec<Rotation> {
+        let mut out = Vec::new();
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            for amt in [Amt::One, Amt::Two, Amt::Rev] {
+                out.push(Rotation { axis, amt });
+            }
+        }
+        out
+    }
+
+    fn solved() -> Cube {
+        Cube::make_solved(Facelet::Green, Facelet::Yellow)
+    }
+
+    #[test]
+    fn mirror_sexy_move() {
+        assert_eq!(
+            mirror(&parse_many("R U R'"), Plane::M),
+            parse_many("L' U' L")
+        );
+        assert_eq!(
+            mirror(&parse_many("R U R'"), Plane::E),
+            parse_many("R' D' R")
+        );
+        assert_eq!(
+            mirror(&parse_many("R U R'"), Plane::S),
+            parse_many("R' U' R")
+        );
+    }
+
+    #[test]
+    fn rotate_sexy_move() {
+        let y = Rotation::try_from("y").unwrap();
+        assert_eq!(rotate(&parse_many("R U R'"), y), parse_many("B U B'"));
+
+        let x = Rotation::try_from("x").unwrap();
+        assert_eq!(rotate(&parse_many("R U R'"), x), parse_many("R F R'"));
+    }
+
+    #[test]
+    fn rotation_parse_round_trip() {
+        for rotation in all_rotations() {
+            let s = rotation.to_string();
+            assert_eq!(Rotation::try_from(s.as_str()), Ok(rotation));
+        }
+
+        assert!(Rotation::try_from("w").is_err());
+        assert!(Rotation::try_from("x3").is_err());
+    }
+
+    #[test]
+    fn rotating_cube_keeps_it_solved() {
+        for rotation in all_rotations() {
+            assert!(solved().rotated(rotation).is_solved());
+            assert_ne!(solved().rotated(rotation), solved());
+            assert_eq!(
+                solved().rotated(rotation).rotated(rotation.inverse()),
+                solved()
+            );
+        }
+
+        // after a y, the old R face (orange) is in front
+        let y = Rotation::try_from("y").unwrap();
+        assert_eq!(
+            solved().rotated(y),
+            Cube::make_solved(Facelet::Orange, Facelet::Yellow)
+        );
+    }
+
+    #[test]
+    fn parse_extended_notation() {
+        assert_eq!(parse_alg("R U R' U'"), parse_many("R U R' U'"));
+        assert_eq!(parse_alg("y R U R'"), parse_many("B U B'"));
+        assert_eq!(parse_alg("x R x'"), parse_many("R"));
+        assert_eq!(parse_alg("r U r'"), parse_many("L F L'"));
+        assert_eq!(parse_alg("Rw U Rw'"), parse_alg("r U r'"));
+        assert_eq!(parse_alg("M2"), parse_many("R2 L2"));
+
+        // wide turns are the same as a face turn plus a slice turn
+        for (wide, split) in [
+            ("r U", "R M' U"),
+            ("l' F", "L' M' F"),
+            ("u R", "U E' R"),
+            ("d2 F", "D2 E2 F"),
+            ("f R", "F S R"),
+            ("b' L", "B' S L"),
+        ] {
+            assert_eq!(
+                solved().apply_many(&parse_alg(wide)),
+                solved().apply_many(&parse_alg(split)),
+                "{wide} vs {split}"
+            );
+        }
+    }
+
+    #[test]
+    fn leftover_rotations_undo_frame() {
+        let x = Rotation::try_from("x").unwrap();
+
+        assert_eq!(leftover_rotations("r U R'"), vec![x]);
+        assert_eq!(leftover_rotations("r U r'"), Vec::new());
+        assert_eq!(leftover_rotations("R U R'"), Vec::new());
+        assert_eq!(leftover_rotations("M2 U M2 U2 M2 U M2"), Vec::new());
+        assert_eq!(leftover_rotations("x y x'").len(), 3);
+
+        // doing the leftovers on a cube is the same as doing the algorithm for real
+        let alg = "R' U R' U' y R' F' R2 U' R' U R' F R F";
+        let cube = solved().apply_many(&parse_alg(alg));
+        let undone = leftover_rotations(alg)
+            .iter()
+            .rev()
+            .map(|r| r.inverse().to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(
+            cube,
+            solved().apply_many(&parse_alg(&format!("{alg} {undone}")))
+        );
+    }
+
+    #[test]
+    fn slice_algs_work() {
+        // H perm and Ua perm, written with slice turns; both are the same as doing nothing
+        // when done twice (or three times), but not before
+        let h = parse_alg("M2 U M2 U2 M2 U M2");
+        assert!(!solved().apply_many(&h).is_solved());
+        assert!(solved().apply_many(&h).apply_many(&h).is_solved());
+
+        let ua = parse_alg("M2 U M U2 M' U M2");
+        let once = solved().apply_many(&ua);
+        assert!(!once.is_solved());
+        assert!(!once.clone().apply_many(&ua).is_solved());
+        assert!(once.apply_many(&ua).apply_many(&ua).is_solved());
+
+        // and the first two layers are left alone, so the D face is still solved
+        assert!(solved().apply_many(&ua).d == solved().d);
+    }
+
+    #[test]
+    fn mirror_matches_cube_model() {
+        for alg in TEST_ALGS {
+            let moves = parse_many(alg);
+
+            for plane in ALL_PLANES {
+                let expected = solved().mirrored(plane).apply_many(&moves).mirrored(plane);
+                let actual = solved().apply_many(&mirror(&moves, plane));
+
+                assert_eq!(actual, expected, "Mirroring {alg} across {plane:?}");
+                assert_eq!(mirror(&mirror(&moves, plane), plane), moves);
+            }
+        }
+    }
+
+    #[test]
+    fn rotate_matches_cube_model() {
+        for alg in TEST_ALGS {
+            let moves = parse_many(alg);
+
+            for rotation in all_rotations() {
+                let expected = solved()
+                    .rotated(rotation)
+                    .apply_many(&moves)
+                    .rotated(rotation.inverse());
+                let actual = solved().apply_many(&rotate(&moves, rotation));
+
+                assert_eq!(actual, expected, "Rotating {alg} by {rotation}");
+            }
+        }
+    }
+}
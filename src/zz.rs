@@ -0,0 +1,217 @@
+//! Module for the first step of ZZ: orienting every edge relative to the F/B axis, while also
+//! solving either the DF and DB edges (EOLine) or the whole cross (EOCross). After that, the rest
+//! of the solve only needs R, U and L turns. Both are solved optimally.
+
+use std::fmt::{Display, Formatter};
+
+use crate::cube::{AmbigFacelet, ColorScheme, Cube, Facelet};
+use crate::dfs_util;
+use crate::edge_orientation_state::AxisEdgeOrientationState;
+use crate::heuristic_caches::{CappedHeuristicCache, Heuristic, HeuristicCache};
+use crate::moves::{to_nice_str, FullMove, ALL_DIRS};
+use crate::shadow::{to_cross, to_line};
+use crate::solve::rotation_to_bottom;
+use crate::transform::{Axis, Rotation};
+
+type MaskedCube = Cube<AmbigFacelet>;
+
+/// What goes along with the edge orientation
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum EoTarget {
+    /// Just the DF and DB edges
+    Line,
+    /// All four D edges
+    Cross,
+}
+
+impl Display for EoTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EoTarget::Line => write!(f, "EOLine"),
+            EoTarget::Cross => write!(f, "EOCross"),
+        }
+    }
+}
+
+/// Optimal EOLine or EOCross; do the rotation (if any) first, to put the color on the bottom,
+/// then the moves
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct EoSolution {
+    pub target: EoTarget,
+    pub color: Facelet,
+    pub rotation: Option<Rotation>,
+    pub moves: Vec<FullMove>,
+}
+
+impl Display for EoSolution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(rotation) = self.rotation {
+            writeln!(f, "Rotation: {rotation}")?;
+        }
+
+        write!(
+            f,
+            "{} ({} moves): {}",
+            self.target,
+            self.moves.len(),
+            to_nice_str(&self.moves)
+        )
+    }
+}
+
+/// Heuristics for EOLine and EOCross. Like the cross caches, everything is recolored to the
+/// Western scheme (white on D) before searching, so these work for any color.
+pub struct ZzCache {
+    edges: HeuristicCache<AxisEdgeOrientationState>,
+    line: HeuristicCache<MaskedCube>,
+    cross: CappedHeuristicCache<MaskedCube>,
+}
+
+impl ZzCache {
+    pub fn initialize() -> Self {
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+
+        Self {
+            // only 2048 of these
+            edges: HeuristicCache::from_goal(
+                AxisEdgeOrientationState::make_solved(Axis::Z),
+                &ALL_DIRS,
+                &[],
+            ),
+            // and only a few hundred of these
+            line: HeuristicCache::from_goal(to_line(solved.clone()), &ALL_DIRS, &[]),
+            cross: CappedHeuristicCache::from_goal(
+                to_cross(solved, &Facelet::White),
+                &ALL_DIRS,
+                &[],
+                5,
+            ),
+        }
+    }
+}
+
+struct EoHeuristic<'a> {
+    cache: &'a ZzCache,
+    target: EoTarget,
+}
+
+impl<'a> Heuristic<(AxisEdgeOrientationState, MaskedCube)> for EoHeuristic<'a> {
+    fn evaluate(&self, state: &(AxisEdgeOrientationState, MaskedCube)) -> usize {
+        let edges = self.cache.edges.evaluate(&state.0);
+
+        let rest = match self.target {
+            EoTarget::Line => self.cache.line.evaluate(&state.1),
+            EoTarget::Cross => self.cache.cross.evaluate(&state.1),
+        };
+
+        edges.max(rest)
+    }
+}
+
+/// Finds the optimal EOLine or EOCross with the given color on the bottom. The edges are oriented
+/// for F/B, so whichever way the cube is facing after the rotation is the way it stays.
+pub fn solve_eo(cube: &Cube, color: &Facelet, target: EoTarget, cache: &ZzCache) -> EoSolution {
+    let rotation = rotation_to_bottom(cube, color);
+
+    let held = match rotation {
+        Some(rotation) => cube.rotated(rotation),
+        None => cube.clone(),
+    };
+
+    assert_eq!(&held.d.cc, color, "Color should be on the bottom");
+
+    let normalized = held.recolored(&ColorScheme::western());
+
+    let edges = AxisEdgeOrientationState::from_cube(&normalized, Axis::Z);
+    let rest = match target {
+        EoTarget::Line => to_line(normalized),
+        EoTarget::Cross => to_cross(normalized, &Facelet::White),
+    };
+
+    // EOLine never takes more than 9 moves, and EOCross not many more than that
+    const MAX_FUEL: usize = 14;
+
+    let moves = dfs_util::solve(
+        (edges, rest),
+        &ALL_DIRS,
+        &[],
+        |(edges, rest): &(AxisEdgeOrientationState, MaskedCube)| {
+            edges.is_solved() && rest.is_solved()
+        },
+        &EoHeuristic { cache, target },
+        MAX_FUEL,
+    );
+
+    EoSolution {
+        target,
+        color: color.clone(),
+        rotation,
+        moves,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::moves::{parse_many, ApplyMove};
+
+    use super::*;
+
+    fn check(cube: &Cube, sol: &EoSolution) {
+        let mut held = cube.clone();
+        if let Some(rotation) = sol.rotation {
+            held = held.rotated(rotation);
+        }
+
+        let done = held.apply_many(&sol.moves);
+        assert_eq!(done.d.cc, sol.color);
+        assert!(AxisEdgeOrientationState::from_cube(&done, Axis::Z).is_solved());
+
+        let rest = match sol.target {
+            EoTarget::Line => to_line(done),
+            EoTarget::Cross => to_cross(done, &sol.color),
+        };
+        assert!(rest.is_solved());
+    }
+
+    #[test]
+    fn solves_eoline_and_eocross() {
+        let cache = ZzCache::initialize();
+
+        let cube = Cube::make_solved(Facelet::Green, Facelet::Yellow)
+            .apply_many(&parse_many("R U2 F' L D2 B R' U F2 L' D B2 U'"));
+
+        let line = solve_eo(&cube, &Facelet::White, EoTarget::Line, &cache);
+        check(&cube, &line);
+        assert_eq!(line.rotation, None);
+
+        let cross = solve_eo(&cube, &Facelet::White, EoTarget::Cross, &cache);
+        check(&cube, &cross);
+
+        // the cross includes the line, so it can't be shorter
+        assert!(line.moves.len() <= cross.moves.len());
+
+        // and a different color gets rotated down first
+        let other = solve_eo(&cube, &Facelet::Blue, EoTarget::Line, &cache);
+        assert!(other.rotation.is_some());
+        check(&cube, &other);
+    }
+
+    #[test]
+    fn short_ones_are_optimal() {
+        let cache = ZzCache::initialize();
+        let solved = Cube::make_solved(Facelet::Green, Facelet::Yellow);
+
+        let sol = solve_eo(&solved, &Facelet::White, EoTarget::Cross, &cache);
+        assert!(sol.moves.is_empty());
+
+        // F flips four edges and breaks the line; F' fixes both
+        let cube = solved.clone().apply_many(&parse_many("F"));
+        let sol = solve_eo(&cube, &Facelet::White, EoTarget::Line, &cache);
+        assert_eq!(sol.moves.len(), 1);
+
+        // R U doesn't flip anything, and doesn't touch the line
+        let cube = solved.apply_many(&parse_many("R U"));
+        let sol = solve_eo(&cube, &Facelet::White, EoTarget::Line, &cache);
+        assert!(sol.moves.is_empty());
+    }
+}